@@ -0,0 +1,79 @@
+//! Per-channel handles onto a shared [`ADS111x`].
+//!
+//! Each [`Channel`] is bound to one [`InputMultiplexer`] setting and
+//! implements [`OneShot`], so code written against that trait can hold "its"
+//! channel without knowing which ADC is behind it. The device is shared
+//! through a [`RefCell`] and should be configured for single-shot mode.
+
+use core::cell::RefCell;
+
+#[cfg(not(feature = "async"))]
+use embedded_hal::i2c::I2c;
+#[cfg(feature = "async")]
+use embedded_hal_async::i2c::I2c;
+
+use crate::{config::InputMultiplexer, error::ADSError, ADS111x};
+
+/// One-shot read of a single ADC channel.
+#[maybe_async_cfg::maybe(
+    sync(cfg(not(feature = "async")),),
+    async(feature = "async"),
+    keep_self
+)]
+#[allow(async_fn_in_trait)]
+pub trait OneShot {
+    type Error;
+
+    /// Run a conversion and return the result in volts.
+    async fn read_voltage(&mut self) -> Result<f32, Self::Error>;
+
+    /// Run a conversion and return the raw conversion code.
+    async fn read_raw(&mut self) -> Result<i16, Self::Error>;
+}
+
+/// Handle for one input of a shared [`ADS111x`].
+pub struct Channel<'a, I2C> {
+    ads: &'a RefCell<ADS111x<I2C>>,
+    mux: InputMultiplexer,
+}
+
+impl<'a, I2C> Channel<'a, I2C> {
+    pub fn new(ads: &'a RefCell<ADS111x<I2C>>, mux: InputMultiplexer) -> Self {
+        Channel { ads, mux }
+    }
+
+    pub fn multiplexer(&self) -> InputMultiplexer {
+        self.mux
+    }
+}
+
+#[maybe_async_cfg::maybe(
+    sync(cfg(not(feature = "async")),),
+    async(feature = "async"),
+    keep_self
+)]
+// The borrow is taken with `try_borrow_mut`, so a second task using the same
+// device while a conversion is in flight gets `DeviceBusy` instead of a panic
+#[allow(clippy::await_holding_refcell_ref)]
+impl<I2C, E> OneShot for Channel<'_, I2C>
+where
+    I2C: I2c<Error = E>,
+{
+    type Error = ADSError<E>;
+
+    async fn read_voltage(&mut self) -> Result<f32, Self::Error> {
+        let mut ads = self
+            .ads
+            .try_borrow_mut()
+            .map_err(|_| ADSError::DeviceBusy)?;
+        ads.read_single_voltage(Some(self.mux)).await
+    }
+
+    async fn read_raw(&mut self) -> Result<i16, Self::Error> {
+        let mut ads = self
+            .ads
+            .try_borrow_mut()
+            .map_err(|_| ADSError::DeviceBusy)?;
+        ads.read_single_raw(Some(self.mux)).await
+    }
+}
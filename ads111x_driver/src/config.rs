@@ -1,5 +1,5 @@
 use bitflags::bitflags;
-use embedded_driver_derive::bitflags::ConfigConversion;
+use embedded_driver_derive::ConfigConversion;
 
 bitflags! {
     #[derive(Debug, Clone, Copy)]
@@ -166,6 +166,27 @@ pub enum DataRate {
     SPS860,
 }
 
+impl DataRate {
+    pub fn samples_per_second(&self) -> u32 {
+        match self {
+            DataRate::SPS8 => 8,
+            DataRate::SPS16 => 16,
+            DataRate::SPS32 => 32,
+            DataRate::SPS64 => 64,
+            DataRate::SPS128 => 128,
+            DataRate::SPS250 => 250,
+            DataRate::SPS475 => 475,
+            DataRate::SPS860 => 860,
+        }
+    }
+
+    /// Nominal duration of a single conversion, rounded up to the next
+    /// microsecond.
+    pub fn conversion_time_us(&self) -> u32 {
+        1_000_000u32.div_ceil(self.samples_per_second())
+    }
+}
+
 #[derive(Debug, Clone, Copy, ConfigConversion)]
 #[config_mask(ADS111xConfig::COMP_MODE_MASK)]
 pub enum ComparatorMode {
@@ -281,11 +302,176 @@ impl ADS111xConfig {
         self
     }
 
+    pub fn multiplexer(&self) -> InputMultiplexer {
+        InputMultiplexer::from(*self)
+    }
+
     pub fn gain_amplifier(&self) -> GainAmplifier {
         GainAmplifier::from(*self)
     }
 
+    pub fn data_rate(&self) -> DataRate {
+        DataRate::from(*self)
+    }
+
+    pub fn comparator_polarity(&self) -> ComparatorPolarity {
+        ComparatorPolarity::from(*self)
+    }
+
+    pub fn comparator_queue(&self) -> ComparatorQueue {
+        ComparatorQueue::from(*self)
+    }
+
     pub(crate) fn operational_status(&self) -> OperationalStatus {
         OperationalStatus::from(*self)
     }
 }
+
+#[cfg(feature = "serde")]
+impl serde::Serialize for ADS111xConfig {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        serializer.serialize_u16(self.bits())
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for ADS111xConfig {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        let bits = u16::deserialize(deserializer)?;
+        let config = ADS111xConfig::from_bits_retain(bits);
+        // The PGA field has two codes aliasing 0.256 V that `GainAmplifier`
+        // cannot represent
+        if bits & Self::PGA_MASK.bits() > Self::PGA_0_256V.bits() {
+            return Err(serde::de::Error::invalid_value(
+                serde::de::Unexpected::Unsigned(bits.into()),
+                &"an ADS111x config register value",
+            ));
+        }
+        Ok(config)
+    }
+}
+
+#[cfg(feature = "defmt")]
+impl defmt::Format for ADS111xConfig {
+    fn format(&self, f: defmt::Formatter) {
+        defmt::write!(
+            f,
+            "ADS111xConfig {{ mux: {}, pga: {}, mode: {}, dr: {}, comp_mode: {}, comp_pol: {}, comp_lat: {}, comp_que: {} }}",
+            InputMultiplexer::from(*self),
+            GainAmplifier::from(*self),
+            Mode::from(*self),
+            DataRate::from(*self),
+            ComparatorMode::from(*self),
+            ComparatorPolarity::from(*self),
+            ComparatorLatching::from(*self),
+            ComparatorQueue::from(*self),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate std;
+
+    use super::*;
+
+    #[test]
+    fn conversion_time_rounds_up() {
+        let times = [
+            (DataRate::SPS8, 125_000),
+            (DataRate::SPS16, 62_500),
+            (DataRate::SPS32, 31_250),
+            (DataRate::SPS64, 15_625),
+            (DataRate::SPS128, 7_813),
+            (DataRate::SPS250, 4_000),
+            (DataRate::SPS475, 2_106),
+            (DataRate::SPS860, 1_163),
+        ];
+        for (data_rate, us) in times {
+            assert_eq!(data_rate.conversion_time_us(), us, "{data_rate:?}");
+        }
+    }
+
+    /// Round-trip every value of a settings enum through a self-describing
+    /// and an index based format, and check that the next index is rejected
+    #[cfg(feature = "serde")]
+    fn assert_serde_round_trip<T>(mask: ADS111xConfig, count: u16)
+    where
+        T: Copy
+            + serde::Serialize
+            + serde::de::DeserializeOwned
+            + From<ADS111xConfig>,
+        ADS111xConfig: From<T>,
+    {
+        let step = mask.bits() & mask.bits().wrapping_neg();
+        for code in 0..count {
+            let bits = code * step;
+            let value = T::from(ADS111xConfig::from_bits_retain(bits));
+
+            let json = serde_json::to_string(&value).unwrap();
+            let decoded: T = serde_json::from_str(&json).unwrap();
+            assert_eq!(ADS111xConfig::from(decoded).bits(), bits, "{json}");
+
+            let mut buf = [0; 1];
+            let index = postcard::to_slice(&value, &mut buf).unwrap();
+            assert_eq!(index, [code as u8]);
+            let decoded: T = postcard::from_bytes(index).unwrap();
+            assert_eq!(ADS111xConfig::from(decoded).bits(), bits, "{json}");
+        }
+
+        assert!(postcard::from_bytes::<T>(&[count as u8]).is_err());
+        assert!(serde_json::from_str::<T>("\"Invalid\"").is_err());
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn settings_round_trip_through_serde() {
+        type Config = ADS111xConfig;
+        assert_serde_round_trip::<OperationalStatus>(Config::OS_MASK, 2);
+        assert_serde_round_trip::<InputMultiplexer>(Config::MUX_MASK, 8);
+        assert_serde_round_trip::<GainAmplifier>(Config::PGA_MASK, 6);
+        assert_serde_round_trip::<Mode>(Config::MODE_MASK, 2);
+        assert_serde_round_trip::<DataRate>(Config::DR_MASK, 8);
+        assert_serde_round_trip::<ComparatorMode>(Config::COMP_MODE_MASK, 2);
+        assert_serde_round_trip::<ComparatorPolarity>(Config::COMP_POL_MASK, 2);
+        assert_serde_round_trip::<ComparatorLatching>(Config::COMP_LAT_MASK, 2);
+        assert_serde_round_trip::<ComparatorQueue>(Config::COMP_QUE_MASK, 4);
+
+        assert_eq!(
+            serde_json::to_string(&InputMultiplexer::AIN1GND).unwrap(),
+            "\"AIN1GND\""
+        );
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn config_serializes_as_register_value() {
+        let config = ADS111xConfig::new()
+            .with_multiplexer(InputMultiplexer::AIN3GND)
+            .with_gain_amplifier(GainAmplifier::V0_256)
+            .with_data_rate(DataRate::SPS860);
+
+        let json = serde_json::to_string(&config).unwrap();
+        assert_eq!(json, std::format!("{}", config.bits()));
+        let decoded: ADS111xConfig = serde_json::from_str(&json).unwrap();
+        assert_eq!(decoded.bits(), config.bits());
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn config_rejects_aliased_gain() {
+        for pga in [0b110, 0b111] {
+            let bits = ADS111xConfig::new().bits()
+                & !ADS111xConfig::PGA_MASK.bits()
+                | pga << 9;
+            let json = std::format!("{bits}");
+            assert!(serde_json::from_str::<ADS111xConfig>(&json).is_err());
+        }
+    }
+}
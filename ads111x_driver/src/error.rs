@@ -1,11 +1,33 @@
+use core::convert::Infallible;
+
 use thiserror::Error;
 
 #[derive(Error, Debug)]
-pub enum ADSError<E> {
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum ADSError<E, PinE = Infallible> {
     #[error("Failed to convert config")]
     ConfigConversionError,
     #[error("Invalid I2C address")]
     WrongAddress,
+    #[error("Device is in use by another channel")]
+    DeviceBusy,
     #[error("I2C communication error: {0}")]
     I2C(#[from] E),
+    #[error("ALERT/RDY pin error")]
+    Pin(PinE),
+}
+
+#[cfg(feature = "async")]
+impl<E> ADSError<E> {
+    /// Re-type a bus-only error so it can be returned alongside pin errors.
+    pub(crate) fn with_pin_error<PinE>(self) -> ADSError<E, PinE> {
+        match self {
+            ADSError::ConfigConversionError => ADSError::ConfigConversionError,
+            ADSError::WrongAddress => ADSError::WrongAddress,
+            ADSError::DeviceBusy => ADSError::DeviceBusy,
+            ADSError::I2C(e) => ADSError::I2C(e),
+            ADSError::Pin(e) => match e {},
+        }
+    }
 }
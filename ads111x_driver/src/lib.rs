@@ -1,7 +1,12 @@
-#![no_std]
+#![cfg_attr(not(feature = "std"), no_std)]
 
+pub mod channel;
 pub mod config;
 pub mod error;
+#[cfg(feature = "async")]
+pub mod multi;
+pub mod record;
+pub mod sample;
 
 #[cfg(not(feature = "async"))]
 use embedded_hal::i2c::I2c;
@@ -9,7 +14,11 @@ use embedded_hal::i2c::I2c;
 use embedded_hal_async::i2c::I2c;
 
 pub use crate::config::ADS111xConfig;
-use crate::{config::*, error::*};
+use crate::{
+    config::*,
+    error::*,
+    sample::{raw_to_voltage, Clock, Sample},
+};
 
 #[maybe_async_cfg::maybe(
     sync(cfg(not(feature = "async")),),
@@ -79,10 +88,15 @@ where
         Ok(config)
     }
 
-    pub async fn read_single_voltage(
+    pub fn config(&self) -> ADS111xConfig {
+        self.config
+    }
+
+    /// Start a single-shot conversion without waiting for it to finish.
+    pub async fn start_single_conversion(
         &mut self,
         mux: Option<InputMultiplexer>,
-    ) -> Result<f32, ADSError<E>> {
+    ) -> Result<(), ADSError<E>> {
         if let Some(m) = mux {
             self.config = self.config.with_multiplexer(m);
         }
@@ -92,18 +106,97 @@ where
             self.config.with_operational_status(OperationalStatus::Busy);
         self.write_config().await?;
 
+        Ok(())
+    }
+
+    pub async fn read_single_voltage(
+        &mut self,
+        mux: Option<InputMultiplexer>,
+    ) -> Result<f32, ADSError<E>> {
+        self.wait_single_conversion(mux).await?;
+        self.read_voltage().await
+    }
+
+    pub async fn read_single_raw(
+        &mut self,
+        mux: Option<InputMultiplexer>,
+    ) -> Result<i16, ADSError<E>> {
+        self.wait_single_conversion(mux).await?;
+        self.read_raw().await
+    }
+
+    async fn wait_single_conversion(
+        &mut self,
+        mux: Option<InputMultiplexer>,
+    ) -> Result<(), ADSError<E>> {
+        self.start_single_conversion(mux).await?;
+
         while !self.check_conversion_ready().await? {
             // Might want to add a small delay here to avoid busy-waiting
             // For now, we'll just continue looping
         }
 
-        self.read_voltage().await
+        Ok(())
     }
 
     pub async fn read_voltage(&mut self) -> Result<f32, ADSError<E>> {
         let val = self.read_raw().await?;
-        let pga = self.config.gain_amplifier().voltage();
-        Ok(f32::from(val) / i16::MAX as f32 * pga)
+        Ok(raw_to_voltage(val, self.config.gain_amplifier()))
+    }
+
+    /// Like [`read_single_voltage`](Self::read_single_voltage), but returns
+    /// the raw code together with the channel, gain and data rate used.
+    pub async fn read_single_sample(
+        &mut self,
+        mux: Option<InputMultiplexer>,
+    ) -> Result<Sample, ADSError<E>> {
+        self.wait_single_conversion(mux).await?;
+        self.read_sample_at(None).await
+    }
+
+    /// Like [`read_single_sample`](Self::read_single_sample), with the
+    /// sample timestamped from `clock` once the conversion has finished.
+    pub async fn read_single_sample_with_clock<C>(
+        &mut self,
+        mux: Option<InputMultiplexer>,
+        clock: &mut C,
+    ) -> Result<Sample, ADSError<E>>
+    where
+        C: Clock,
+    {
+        self.wait_single_conversion(mux).await?;
+        self.read_sample_at(Some(clock.now_us())).await
+    }
+
+    /// Read the latest conversion result in continuous mode as a [`Sample`].
+    pub async fn read_sample(&mut self) -> Result<Sample, ADSError<E>> {
+        self.read_sample_at(None).await
+    }
+
+    /// Like [`read_sample`](Self::read_sample), with the sample timestamped
+    /// from `clock`.
+    pub async fn read_sample_with_clock<C>(
+        &mut self,
+        clock: &mut C,
+    ) -> Result<Sample, ADSError<E>>
+    where
+        C: Clock,
+    {
+        self.read_sample_at(Some(clock.now_us())).await
+    }
+
+    async fn read_sample_at(
+        &mut self,
+        timestamp_us: Option<u64>,
+    ) -> Result<Sample, ADSError<E>> {
+        let raw = self.read_raw().await?;
+        Ok(Sample {
+            timestamp_us,
+            channel: self.config.multiplexer(),
+            gain: self.config.gain_amplifier(),
+            data_rate: self.config.data_rate(),
+            raw,
+        })
     }
 
     pub async fn read_raw(&mut self) -> Result<i16, ADSError<E>> {
@@ -134,6 +227,25 @@ where
             .await
     }
 
+    /// Use the ALERT/RDY pin as a conversion-ready signal, as described in
+    /// the datasheet: the threshold MSBs are set to 1 (high) and 0 (low) and
+    /// the comparator is enabled if it was disabled.
+    pub async fn enable_conversion_ready_pin(
+        &mut self,
+    ) -> Result<(), ADSError<E>> {
+        self.write_high_treshold(i16::MIN).await?;
+        self.write_low_treshold(0).await?;
+
+        if matches!(self.config.comparator_queue(), ComparatorQueue::Disable) {
+            self.config = self
+                .config
+                .with_comparator_queue(ComparatorQueue::AsserAfterOne);
+            self.write_config().await?;
+        }
+
+        Ok(())
+    }
+
     pub async fn write_low_treshold(
         &mut self,
         low_tresh: i16,
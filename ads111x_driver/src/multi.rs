@@ -0,0 +1,437 @@
+//! Single-shot reads across several devices at once.
+//!
+//! The conversions are started back to back and then run in parallel on the
+//! chips, so reading `N` devices takes roughly one conversion time instead of
+//! `N`. The devices may sit on separate buses or share one through a bus
+//! manager. They are passed as an array, so every handle has the same `I2C`
+//! type; devices on buses of different types can be wrapped in one type
+//! implementing [`I2c`], or read with one call per bus type.
+
+use embassy_futures::join::join_array;
+use embedded_hal::digital::ErrorType;
+use embedded_hal_async::{delay::DelayNs, digital::Wait, i2c::I2c};
+
+use crate::{config::ComparatorPolarity, error::ADSError, ADS111x};
+
+/// Interval between conversion-ready polls once the nominal conversion time
+/// has passed.
+const POLL_INTERVAL_US: u32 = 100;
+
+/// Start a single-shot conversion on every device, wait until all of them
+/// are done by polling the operational status and return the voltages in
+/// device order.
+///
+/// Each pass polls every pending device whose nominal conversion time has
+/// passed and reads those that are done, so a fast device is read while a
+/// slow one is still converting. Between passes the delay waits until the
+/// next pending device is due, or 100 µs if they all are.
+pub async fn read_single_voltages<I2C, E, D, const N: usize>(
+    mut devices: [&mut ADS111x<I2C>; N],
+    delay: &mut D,
+) -> Result<[f32; N], ADSError<E>>
+where
+    I2C: I2c<Error = E>,
+    D: DelayNs,
+{
+    for ads in devices.iter_mut() {
+        ads.start_single_conversion(None).await?;
+    }
+
+    let conversion_times_us = devices
+        .each_ref()
+        .map(|ads| ads.config().data_rate().conversion_time_us());
+    let mut voltages = [None; N];
+    let mut elapsed_us = 0;
+    // Nominal end of the next pending conversion
+    while let Some(due_us) = conversion_times_us
+        .iter()
+        .zip(&voltages)
+        .filter(|(_, voltage)| voltage.is_none())
+        .map(|(&time_us, _)| time_us)
+        .min()
+    {
+        let wait_us = due_us.saturating_sub(elapsed_us).max(POLL_INTERVAL_US);
+        delay.delay_us(wait_us).await;
+        elapsed_us += wait_us;
+
+        for ((ads, voltage), &time_us) in devices
+            .iter_mut()
+            .zip(voltages.iter_mut())
+            .zip(&conversion_times_us)
+        {
+            if voltage.is_none()
+                && time_us <= elapsed_us
+                && ads.check_conversion_ready().await?
+            {
+                *voltage = Some(ads.read_voltage().await?);
+            }
+        }
+    }
+
+    Ok(voltages.map(Option::unwrap_or_default))
+}
+
+/// Start a single-shot conversion on every device, wait for all ALERT/RDY
+/// pins to assert and read the results, returned in device order.
+///
+/// The devices must have been set up with
+/// [`ADS111x::enable_conversion_ready_pin`]. The pins are waited on
+/// concurrently. If waiting on a pin fails, the other waits still run to
+/// completion before the first error is returned.
+pub async fn read_single_voltages_with_alert<I2C, E, P, PinE, const N: usize>(
+    mut devices: [&mut ADS111x<I2C>; N],
+    alerts: [&mut P; N],
+) -> Result<[f32; N], ADSError<E, PinE>>
+where
+    I2C: I2c<Error = E>,
+    P: Wait + ErrorType<Error = PinE>,
+{
+    for ads in devices.iter_mut() {
+        ads.start_single_conversion(None)
+            .await
+            .map_err(ADSError::with_pin_error)?;
+    }
+
+    let mut index = 0;
+    let waits = alerts.map(|alert| {
+        let polarity = devices[index].config().comparator_polarity();
+        index += 1;
+        wait_ready(alert, polarity)
+    });
+    for result in join_array(waits).await {
+        result.map_err(ADSError::Pin)?;
+    }
+
+    let mut voltages = [0.0; N];
+    for (ads, voltage) in devices.iter_mut().zip(voltages.iter_mut()) {
+        *voltage =
+            ads.read_voltage().await.map_err(ADSError::with_pin_error)?;
+    }
+
+    Ok(voltages)
+}
+
+/// Wait for the ALERT/RDY pin to signal a finished conversion
+async fn wait_ready<P: Wait>(
+    alert: &mut P,
+    polarity: ComparatorPolarity,
+) -> Result<(), P::Error> {
+    match polarity {
+        ComparatorPolarity::ActiveLow => alert.wait_for_low().await,
+        ComparatorPolarity::ActiveHigh => alert.wait_for_high().await,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use core::{
+        cell::Cell,
+        convert::Infallible,
+        future::{poll_fn, Future},
+        pin::pin,
+        task::{Context, Poll, Waker},
+    };
+
+    use embedded_hal::{digital, i2c::ErrorKind};
+    use embedded_hal_async::i2c::{self, Operation};
+
+    use super::*;
+    use crate::{
+        config::{DataRate, GainAmplifier},
+        ADS111xConfig,
+    };
+
+    fn block_on<F: Future>(future: F) -> F::Output {
+        let mut future = pin!(future);
+        let mut cx = Context::from_waker(Waker::noop());
+        loop {
+            if let Poll::Ready(output) = future.as_mut().poll(&mut cx) {
+                return output;
+            }
+        }
+    }
+
+    /// Chip answering config reads as busy `busy_polls` times before the
+    /// conversion is done
+    struct FakeAds {
+        config: [u8; 2],
+        register: u8,
+        busy_polls: u32,
+        status_reads: u32,
+        raw: i16,
+    }
+
+    impl FakeAds {
+        fn new(busy_polls: u32, raw: i16) -> Self {
+            Self {
+                config: [0; 2],
+                register: 0,
+                busy_polls,
+                status_reads: 0,
+                raw,
+            }
+        }
+    }
+
+    impl i2c::ErrorType for FakeAds {
+        type Error = ErrorKind;
+    }
+
+    impl i2c::I2c for FakeAds {
+        async fn transaction(
+            &mut self,
+            _address: u8,
+            operations: &mut [Operation<'_>],
+        ) -> Result<(), ErrorKind> {
+            for operation in operations {
+                match operation {
+                    Operation::Write([register, config @ ..]) => {
+                        self.register = *register;
+                        if let Ok(config) = <[u8; 2]>::try_from(config) {
+                            self.config = config;
+                        }
+                    }
+                    Operation::Read(buf) if self.register == 0 => {
+                        buf.copy_from_slice(&self.raw.to_be_bytes());
+                    }
+                    Operation::Read(buf) => {
+                        self.status_reads += 1;
+                        let mut config = u16::from_be_bytes(self.config);
+                        if self.busy_polls == 0 {
+                            config |= ADS111xConfig::OS_NOT_BUSY.bits();
+                        } else {
+                            config &= !ADS111xConfig::OS_MASK.bits();
+                            self.busy_polls -= 1;
+                        }
+                        buf.copy_from_slice(&config.to_be_bytes());
+                    }
+                    Operation::Write(_) => {}
+                }
+            }
+            Ok(())
+        }
+    }
+
+    /// Delay adding up the time it was asked to wait
+    #[derive(Default)]
+    struct TotalDelay {
+        ns: u64,
+    }
+
+    impl DelayNs for TotalDelay {
+        async fn delay_ns(&mut self, ns: u32) {
+            self.ns += u64::from(ns);
+        }
+    }
+
+    /// ALERT/RDY pin that asserts or fails after being polled
+    /// `pending_polls` times. Every poll advances the shared `ticks`, so the
+    /// tick a pin asserted at shows the order the waits completed in.
+    struct FakeAlert<'a> {
+        fail: bool,
+        pending_polls: u32,
+        ticks: &'a Cell<u32>,
+        waited_for_high: Option<bool>,
+        asserted_at: Option<u32>,
+    }
+
+    impl<'a> FakeAlert<'a> {
+        fn new(ticks: &'a Cell<u32>, pending_polls: u32, fail: bool) -> Self {
+            Self {
+                fail,
+                pending_polls,
+                ticks,
+                waited_for_high: None,
+                asserted_at: None,
+            }
+        }
+
+        async fn wait(&mut self, high: bool) -> Result<(), digital::ErrorKind> {
+            self.waited_for_high = Some(high);
+            poll_fn(|cx| {
+                self.ticks.set(self.ticks.get() + 1);
+                if self.pending_polls == 0 {
+                    return Poll::Ready(());
+                }
+                self.pending_polls -= 1;
+                cx.waker().wake_by_ref();
+                Poll::Pending
+            })
+            .await;
+            self.asserted_at = Some(self.ticks.get());
+
+            if self.fail {
+                Err(digital::ErrorKind::Other)
+            } else {
+                Ok(())
+            }
+        }
+    }
+
+    impl ErrorType for FakeAlert<'_> {
+        type Error = digital::ErrorKind;
+    }
+
+    impl Wait for FakeAlert<'_> {
+        async fn wait_for_high(&mut self) -> Result<(), digital::ErrorKind> {
+            self.wait(true).await
+        }
+
+        async fn wait_for_low(&mut self) -> Result<(), digital::ErrorKind> {
+            self.wait(false).await
+        }
+
+        async fn wait_for_rising_edge(
+            &mut self,
+        ) -> Result<(), digital::ErrorKind> {
+            self.wait(true).await
+        }
+
+        async fn wait_for_falling_edge(
+            &mut self,
+        ) -> Result<(), digital::ErrorKind> {
+            self.wait(false).await
+        }
+
+        async fn wait_for_any_edge(
+            &mut self,
+        ) -> Result<(), digital::ErrorKind> {
+            self.wait(true).await
+        }
+    }
+
+    fn ads(
+        fake: FakeAds,
+        data_rate: DataRate,
+        polarity: ComparatorPolarity,
+    ) -> ADS111x<FakeAds> {
+        let config = ADS111xConfig::new()
+            .with_data_rate(data_rate)
+            .with_gain_amplifier(GainAmplifier::V4_096)
+            .with_comparator_polarity(polarity);
+        ADS111x::new(fake, 0x48, config).unwrap()
+    }
+
+    #[test]
+    fn waits_for_slowest_data_rate_then_polls() {
+        let polarity = ComparatorPolarity::ActiveLow;
+        let mut fast =
+            ads(FakeAds::new(0, i16::MAX), DataRate::SPS860, polarity);
+        let mut slow =
+            ads(FakeAds::new(2, -i16::MAX), DataRate::SPS8, polarity);
+        let mut delay = TotalDelay::default();
+
+        let voltages =
+            block_on(read_single_voltages([&mut fast, &mut slow], &mut delay))
+                .unwrap();
+
+        assert_eq!(voltages, [4.096, -4.096]);
+        // 125 ms for 8 SPS, plus two polls of the busy device
+        assert_eq!(
+            delay.ns,
+            (125_000 + 2 * u64::from(POLL_INTERVAL_US)) * 1000
+        );
+    }
+
+    #[test]
+    fn polls_devices_once_they_are_due() {
+        let polarity = ComparatorPolarity::ActiveLow;
+        let mut fast = ads(FakeAds::new(1, 0), DataRate::SPS860, polarity);
+        let mut slow = ads(FakeAds::new(0, 0), DataRate::SPS8, polarity);
+        let mut delay = TotalDelay::default();
+
+        block_on(read_single_voltages([&mut slow, &mut fast], &mut delay))
+            .unwrap();
+
+        // The fast device is polled until done, then the slow one once its
+        // conversion time has passed
+        assert_eq!(fast.destroy().status_reads, 2);
+        assert_eq!(slow.destroy().status_reads, 1);
+        assert_eq!(delay.ns, 125_000 * 1000);
+    }
+
+    #[test]
+    fn waits_for_alert_with_configured_polarity() {
+        let mut low = ads(
+            FakeAds::new(0, i16::MAX),
+            DataRate::SPS128,
+            ComparatorPolarity::ActiveLow,
+        );
+        let mut high = ads(
+            FakeAds::new(0, 0),
+            DataRate::SPS128,
+            ComparatorPolarity::ActiveHigh,
+        );
+        let ticks = Cell::new(0);
+        let mut low_alert = FakeAlert::new(&ticks, 0, false);
+        let mut high_alert = FakeAlert::new(&ticks, 0, false);
+
+        let voltages = block_on(read_single_voltages_with_alert(
+            [&mut low, &mut high],
+            [&mut low_alert, &mut high_alert],
+        ))
+        .unwrap();
+
+        assert_eq!(voltages, [4.096, 0.0]);
+        assert_eq!(low_alert.waited_for_high, Some(false));
+        assert_eq!(high_alert.waited_for_high, Some(true));
+    }
+
+    #[test]
+    fn alert_waits_run_concurrently() {
+        let polarity = ComparatorPolarity::ActiveLow;
+        let mut slow = ads(FakeAds::new(0, 0), DataRate::SPS8, polarity);
+        let mut fast = ads(FakeAds::new(0, 0), DataRate::SPS860, polarity);
+        let ticks = Cell::new(0);
+        let mut slow_alert = FakeAlert::new(&ticks, 3, false);
+        let mut fast_alert = FakeAlert::new(&ticks, 0, false);
+
+        block_on(read_single_voltages_with_alert(
+            [&mut slow, &mut fast],
+            [&mut slow_alert, &mut fast_alert],
+        ))
+        .unwrap();
+
+        // The second pin is not held up by the first one
+        assert!(fast_alert.asserted_at < slow_alert.asserted_at);
+    }
+
+    #[test]
+    fn alert_pin_errors_are_returned() {
+        let mut ads = ads(
+            FakeAds::new(0, 0),
+            DataRate::SPS128,
+            ComparatorPolarity::ActiveLow,
+        );
+        let ticks = Cell::new(0);
+        let mut alert = FakeAlert::new(&ticks, 0, true);
+
+        let result =
+            block_on(read_single_voltages_with_alert([&mut ads], [&mut alert]));
+
+        assert!(matches!(
+            result,
+            Err(ADSError::Pin(digital::ErrorKind::Other))
+        ));
+    }
+
+    #[test]
+    fn with_pin_error_keeps_bus_errors() {
+        type BusError = ADSError<ErrorKind>;
+        let retype = |e: BusError| e.with_pin_error::<Infallible>();
+
+        assert!(matches!(
+            retype(ADSError::ConfigConversionError),
+            ADSError::ConfigConversionError
+        ));
+        assert!(matches!(
+            retype(ADSError::WrongAddress),
+            ADSError::WrongAddress
+        ));
+        assert!(matches!(retype(ADSError::DeviceBusy), ADSError::DeviceBusy));
+        assert!(matches!(
+            retype(ADSError::I2C(ErrorKind::Overrun)),
+            ADSError::I2C(ErrorKind::Overrun)
+        ));
+    }
+}
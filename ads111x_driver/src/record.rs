@@ -0,0 +1,330 @@
+//! Compact binary record format for [`Sample`]s.
+//!
+//! Every record is self-describing so a log can be written to UART or flash
+//! one sample at a time and parsed back without any framing around it:
+//!
+//! | Offset | Size | Content                                                 |
+//! |--------|------|---------------------------------------------------------|
+//! | 0      | 1    | Format version, currently [`RECORD_VERSION`]            |
+//! | 1      | 2    | Settings, big endian: the MUX, PGA and DR bits at their |
+//! |        |      | config register positions, bit 0 set if timestamped     |
+//! | 3      | 2    | Raw conversion code, big endian                         |
+//! | 5      | 8    | Timestamp in microseconds, big endian, if present       |
+//!
+//! Encoding is `no_std`; the CSV conversion needs the `std` feature.
+
+use thiserror::Error;
+
+use crate::{
+    config::{DataRate, GainAmplifier, InputMultiplexer},
+    sample::Sample,
+    ADS111xConfig,
+};
+
+/// Version written by [`encode`] and accepted by [`decode`].
+pub const RECORD_VERSION: u8 = 1;
+
+/// Length of a record without timestamp.
+pub const RECORD_LEN: usize = 5;
+
+/// Length of a record with timestamp, and so the largest record.
+pub const MAX_RECORD_LEN: usize = RECORD_LEN + 8;
+
+const TIMESTAMP_FLAG: u16 = 1;
+
+#[derive(Error, Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum RecordError {
+    #[error("Unsupported record version {0}")]
+    UnsupportedVersion(u8),
+    #[error("Record is truncated")]
+    Truncated,
+    #[error("Invalid settings in record: {0:#06x}")]
+    InvalidSettings(u16),
+}
+
+/// Encode `sample` into `buf` and return the used part of it.
+pub fn encode<'a>(
+    sample: &Sample,
+    buf: &'a mut [u8; MAX_RECORD_LEN],
+) -> &'a [u8] {
+    let mut settings = ADS111xConfig::empty()
+        .with_multiplexer(sample.channel)
+        .with_gain_amplifier(sample.gain)
+        .with_data_rate(sample.data_rate)
+        .bits();
+    if sample.timestamp_us.is_some() {
+        settings |= TIMESTAMP_FLAG;
+    }
+
+    buf[0] = RECORD_VERSION;
+    buf[1..3].copy_from_slice(&settings.to_be_bytes());
+    buf[3..5].copy_from_slice(&sample.raw.to_be_bytes());
+
+    match sample.timestamp_us {
+        Some(timestamp_us) => {
+            buf[RECORD_LEN..].copy_from_slice(&timestamp_us.to_be_bytes());
+            &buf[..MAX_RECORD_LEN]
+        }
+        None => &buf[..RECORD_LEN],
+    }
+}
+
+/// Decode the record at the start of `bytes`. Returns the sample and the
+/// number of bytes it took up.
+pub fn decode(bytes: &[u8]) -> Result<(Sample, usize), RecordError> {
+    let header = bytes.get(..RECORD_LEN).ok_or(RecordError::Truncated)?;
+    if header[0] != RECORD_VERSION {
+        return Err(RecordError::UnsupportedVersion(header[0]));
+    }
+
+    let settings = u16::from_be_bytes([header[1], header[2]]);
+    let config_bits = settings & !TIMESTAMP_FLAG;
+    let mask = ADS111xConfig::MUX_MASK
+        | ADS111xConfig::PGA_MASK
+        | ADS111xConfig::DR_MASK;
+    // PGA codes above 0.256 V are aliases the driver never writes
+    if config_bits & !mask.bits() != 0
+        || config_bits & ADS111xConfig::PGA_MASK.bits()
+            > ADS111xConfig::PGA_0_256V.bits()
+    {
+        return Err(RecordError::InvalidSettings(settings));
+    }
+    let config = ADS111xConfig::from_bits_retain(config_bits);
+
+    let raw = i16::from_be_bytes([header[3], header[4]]);
+
+    let (timestamp_us, len) = if settings & TIMESTAMP_FLAG != 0 {
+        let timestamp = bytes
+            .get(RECORD_LEN..MAX_RECORD_LEN)
+            .ok_or(RecordError::Truncated)?;
+        let mut be = [0; 8];
+        be.copy_from_slice(timestamp);
+        (Some(u64::from_be_bytes(be)), MAX_RECORD_LEN)
+    } else {
+        (None, RECORD_LEN)
+    };
+
+    Ok((
+        Sample {
+            timestamp_us,
+            channel: InputMultiplexer::from(config),
+            gain: GainAmplifier::from(config),
+            data_rate: DataRate::from(config),
+            raw,
+        },
+        len,
+    ))
+}
+
+/// Convert a log of back-to-back records to CSV, one row per sample. Returns
+/// the number of samples written.
+///
+/// A trailing partial record, as left behind by an interrupted write, is
+/// ignored.
+#[cfg(feature = "std")]
+pub fn log_to_csv<R, W>(mut reader: R, mut writer: W) -> std::io::Result<usize>
+where
+    R: std::io::Read,
+    W: std::io::Write,
+{
+    use std::io::{Error, ErrorKind};
+
+    let mut log = std::vec::Vec::new();
+    reader.read_to_end(&mut log)?;
+
+    writeln!(
+        writer,
+        "timestamp_us,channel,gain_v,data_rate_sps,raw,voltage"
+    )?;
+
+    let mut bytes = log.as_slice();
+    let mut count = 0;
+    while !bytes.is_empty() {
+        let (sample, len) = match decode(bytes) {
+            Ok(record) => record,
+            Err(RecordError::Truncated) => break,
+            Err(e) => return Err(Error::new(ErrorKind::InvalidData, e)),
+        };
+
+        if let Some(timestamp_us) = sample.timestamp_us {
+            write!(writer, "{}", timestamp_us)?;
+        }
+        writeln!(
+            writer,
+            ",{:?},{},{},{},{}",
+            sample.channel,
+            sample.gain.voltage(),
+            sample.data_rate.samples_per_second(),
+            sample.raw,
+            sample.voltage()
+        )?;
+
+        bytes = &bytes[len..];
+        count += 1;
+    }
+
+    Ok(count)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample(timestamp_us: Option<u64>) -> Sample {
+        Sample {
+            timestamp_us,
+            channel: InputMultiplexer::AIN1GND,
+            gain: GainAmplifier::V0_256,
+            data_rate: DataRate::SPS860,
+            raw: -2,
+        }
+    }
+
+    /// Config register bits of the settings of `sample`
+    fn settings(sample: &Sample) -> u16 {
+        ADS111xConfig::from(sample.channel).bits()
+            | ADS111xConfig::from(sample.gain).bits()
+            | ADS111xConfig::from(sample.data_rate).bits()
+    }
+
+    fn assert_same(decoded: &Sample, expected: &Sample) {
+        assert_eq!(decoded.timestamp_us, expected.timestamp_us);
+        assert_eq!(settings(decoded), settings(expected));
+        assert_eq!(decoded.raw, expected.raw);
+    }
+
+    #[test]
+    fn round_trips_without_timestamp() {
+        let sample = sample(None);
+        let mut buf = [0; MAX_RECORD_LEN];
+        let record = encode(&sample, &mut buf);
+        assert_eq!(record, [RECORD_VERSION, 0x5A, 0xE0, 0xFF, 0xFE]);
+
+        let (decoded, len) = decode(record).unwrap();
+        assert_eq!(len, RECORD_LEN);
+        assert_same(&decoded, &sample);
+    }
+
+    #[test]
+    fn round_trips_with_timestamp() {
+        let sample = sample(Some(0x0102_0304_0506_0708));
+        let mut buf = [0; MAX_RECORD_LEN];
+        let record = encode(&sample, &mut buf);
+        assert_eq!(
+            record,
+            [
+                RECORD_VERSION,
+                0x5A,
+                0xE1,
+                0xFF,
+                0xFE,
+                1,
+                2,
+                3,
+                4,
+                5,
+                6,
+                7,
+                8
+            ]
+        );
+
+        let (decoded, len) = decode(record).unwrap();
+        assert_eq!(len, MAX_RECORD_LEN);
+        assert_same(&decoded, &sample);
+    }
+
+    #[test]
+    fn decodes_back_to_back_records() {
+        let mut log = [0; RECORD_LEN + MAX_RECORD_LEN];
+        let mut buf = [0; MAX_RECORD_LEN];
+        log[..RECORD_LEN].copy_from_slice(encode(&sample(None), &mut buf));
+        log[RECORD_LEN..].copy_from_slice(encode(&sample(Some(7)), &mut buf));
+
+        let (first, len) = decode(&log).unwrap();
+        assert_eq!((first.timestamp_us, len), (None, RECORD_LEN));
+        let (second, _) = decode(&log[len..]).unwrap();
+        assert_eq!(second.timestamp_us, Some(7));
+    }
+
+    #[test]
+    fn rejects_other_versions() {
+        assert_eq!(
+            decode(&[2, 0, 0, 0, 0]).unwrap_err(),
+            RecordError::UnsupportedVersion(2)
+        );
+    }
+
+    #[test]
+    fn rejects_truncated_records() {
+        let mut buf = [0; MAX_RECORD_LEN];
+        let record = encode(&sample(Some(7)), &mut buf);
+
+        assert_eq!(decode(&[]).unwrap_err(), RecordError::Truncated);
+        assert_eq!(
+            decode(&record[..RECORD_LEN - 1]).unwrap_err(),
+            RecordError::Truncated
+        );
+        assert_eq!(
+            decode(&record[..MAX_RECORD_LEN - 1]).unwrap_err(),
+            RecordError::Truncated
+        );
+    }
+
+    #[test]
+    fn rejects_invalid_settings() {
+        // PGA codes 0b110 and 0b111 alias 0.256 V
+        for pga in [0b110, 0b111] {
+            let settings = pga << 9;
+            let [high, low] = u16::to_be_bytes(settings);
+            assert_eq!(
+                decode(&[RECORD_VERSION, high, low, 0, 0]).unwrap_err(),
+                RecordError::InvalidSettings(settings)
+            );
+        }
+
+        // Mode and comparator bits are not part of a record
+        let settings = ADS111xConfig::MODE_SINGLE.bits();
+        let [high, low] = u16::to_be_bytes(settings);
+        assert_eq!(
+            decode(&[RECORD_VERSION, high, low, 0, 0]).unwrap_err(),
+            RecordError::InvalidSettings(settings)
+        );
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn converts_log_to_csv() {
+        use std::{string::String, vec::Vec};
+
+        let mut log = Vec::new();
+        let mut buf = [0; MAX_RECORD_LEN];
+        log.extend_from_slice(encode(&sample(Some(1500)), &mut buf));
+        log.extend_from_slice(encode(&sample(None), &mut buf));
+        // Interrupted write of a third record
+        log.extend_from_slice(&[RECORD_VERSION, 0x5A]);
+
+        let mut csv = Vec::new();
+        assert_eq!(log_to_csv(log.as_slice(), &mut csv).unwrap(), 2);
+
+        let voltage = sample(None).voltage();
+        assert_eq!(
+            String::from_utf8(csv).unwrap(),
+            std::format!(
+                "timestamp_us,channel,gain_v,data_rate_sps,raw,voltage\n\
+                 1500,AIN1GND,0.256,860,-2,{voltage}\n\
+                 ,AIN1GND,0.256,860,-2,{voltage}\n"
+            )
+        );
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn csv_conversion_fails_on_invalid_records() {
+        let log = [2, 0, 0, 0, 0];
+        let error = log_to_csv(log.as_slice(), std::io::sink()).unwrap_err();
+        assert_eq!(error.kind(), std::io::ErrorKind::InvalidData);
+    }
+}
@@ -0,0 +1,67 @@
+//! Conversion results with the settings they were taken with.
+
+use crate::config::{DataRate, GainAmplifier, InputMultiplexer};
+
+/// Monotonic time source used to timestamp samples.
+///
+/// The epoch is up to the implementation, only differences between two
+/// readings are meaningful.
+pub trait Clock {
+    /// Current time in microseconds.
+    fn now_us(&mut self) -> u64;
+}
+
+/// A single conversion result.
+#[derive(Debug, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Sample {
+    /// Time the conversion was picked up, if a [`Clock`] was given.
+    pub timestamp_us: Option<u64>,
+    pub channel: InputMultiplexer,
+    pub gain: GainAmplifier,
+    pub data_rate: DataRate,
+    pub raw: i16,
+}
+
+impl Sample {
+    pub fn voltage(&self) -> f32 {
+        raw_to_voltage(self.raw, self.gain)
+    }
+}
+
+pub(crate) fn raw_to_voltage(raw: i16, gain: GainAmplifier) -> f32 {
+    f32::from(raw) / i16::MAX as f32 * gain.voltage()
+}
+
+#[cfg(all(test, feature = "serde"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn round_trips_through_serde() {
+        let sample = Sample {
+            timestamp_us: Some(1500),
+            channel: InputMultiplexer::AIN2GND,
+            gain: GainAmplifier::V1_024,
+            data_rate: DataRate::SPS250,
+            raw: -300,
+        };
+
+        let json = serde_json::to_string(&sample).unwrap();
+        assert_eq!(
+            json,
+            r#"{"timestamp_us":1500,"channel":"AIN2GND","gain":"V1_024","data_rate":"SPS250","raw":-300}"#
+        );
+
+        let decoded: Sample = serde_json::from_str(&json).unwrap();
+        assert_eq!(decoded.timestamp_us, sample.timestamp_us);
+        assert_eq!(decoded.raw, sample.raw);
+        assert_eq!(decoded.voltage(), sample.voltage());
+        assert!(matches!(decoded.channel, InputMultiplexer::AIN2GND));
+        assert!(matches!(decoded.data_rate, DataRate::SPS250));
+
+        let unknown_gain = json.replace("V1_024", "V0_128");
+        assert!(serde_json::from_str::<Sample>(&unknown_gain).is_err());
+    }
+}
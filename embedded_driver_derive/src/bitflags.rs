@@ -2,7 +2,8 @@ use proc_macro::TokenStream;
 use quote::quote;
 use syn::{parse_macro_input, Data, DeriveInput};
 
-#[proc_macro_derive(ConfigConversion, attributes(config_mask, config_flag))]
+use crate::{defmt, serde};
+
 pub fn derive_config_conversion(input: TokenStream) -> TokenStream {
     let input = parse_macro_input!(input as DeriveInput);
     let name = input.ident;
@@ -29,6 +30,9 @@ pub fn derive_config_conversion(input: TokenStream) -> TokenStream {
         }
     });
 
+    let serde_impls = serde::unit_enum_impls(&name, variants);
+    let defmt_impl = defmt::unit_enum_impl(&name, variants);
+
     let expanded = quote! {
         impl From<#name> for ADS111xConfig {
             fn from(value: #name) -> Self {
@@ -47,6 +51,10 @@ pub fn derive_config_conversion(input: TokenStream) -> TokenStream {
                 }
             }
         }
+
+        #serde_impls
+
+        #defmt_impl
     };
 
     TokenStream::from(expanded)
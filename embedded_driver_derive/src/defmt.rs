@@ -0,0 +1,28 @@
+use proc_macro2::{Ident, TokenStream};
+use quote::quote;
+use syn::{punctuated::Punctuated, token::Comma, Variant};
+
+/// `defmt::Format` for a fieldless enum, printing the variant name.
+pub fn unit_enum_impl(
+    name: &Ident,
+    variants: &Punctuated<Variant, Comma>,
+) -> TokenStream {
+    let arms = variants.iter().map(|v| {
+        let variant = &v.ident;
+        let variant_str = variant.to_string();
+        quote! {
+            #name::#variant => ::defmt::write!(f, #variant_str),
+        }
+    });
+
+    quote! {
+        #[cfg(feature = "defmt")]
+        impl ::defmt::Format for #name {
+            fn format(&self, f: ::defmt::Formatter) {
+                match self {
+                    #(#arms)*
+                }
+            }
+        }
+    }
+}
@@ -1 +1,14 @@
-pub mod bitflags;
+use proc_macro::TokenStream;
+
+mod bitflags;
+mod defmt;
+mod serde;
+
+/// Conversion between a settings enum and its bits in `ADS111xConfig`.
+///
+/// Also emits `serde` and `defmt` impls for the enum, gated on the features of
+/// the same name in the crate using the derive.
+#[proc_macro_derive(ConfigConversion, attributes(config_mask, config_flag))]
+pub fn derive_config_conversion(input: TokenStream) -> TokenStream {
+    bitflags::derive_config_conversion(input)
+}
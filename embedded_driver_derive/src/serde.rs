@@ -0,0 +1,124 @@
+use proc_macro2::{Ident, Literal, TokenStream};
+use quote::quote;
+use syn::{punctuated::Punctuated, token::Comma, Variant};
+
+/// `Serialize` and `Deserialize` for a fieldless enum, matching what
+/// `#[derive(Serialize, Deserialize)]` from serde would produce: unit
+/// variants identified by name or by index.
+pub fn unit_enum_impls(
+    name: &Ident,
+    variants: &Punctuated<Variant, Comma>,
+) -> TokenStream {
+    let name_str = name.to_string();
+    let idents: Vec<_> = variants.iter().map(|v| &v.ident).collect();
+    let names: Vec<_> = idents.iter().map(|v| v.to_string()).collect();
+    let indices: Vec<_> = (0..idents.len())
+        .map(|i| Literal::u32_unsuffixed(i as u32))
+        .collect();
+    let indices_u64: Vec<_> = (0..idents.len())
+        .map(|i| Literal::u64_unsuffixed(i as u64))
+        .collect();
+    let expecting = format!("variant identifier of {}", name_str);
+
+    quote! {
+        #[cfg(feature = "serde")]
+        const _: () = {
+            impl ::serde::Serialize for #name {
+                fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+                where
+                    S: ::serde::Serializer,
+                {
+                    match self {
+                        #(#name::#idents => serializer.serialize_unit_variant(
+                            #name_str,
+                            #indices,
+                            #names,
+                        ),)*
+                    }
+                }
+            }
+
+            impl<'de> ::serde::Deserialize<'de> for #name {
+                fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+                where
+                    D: ::serde::Deserializer<'de>,
+                {
+                    const VARIANTS: &[&str] = &[#(#names),*];
+
+                    struct VariantVisitor;
+
+                    impl<'de> ::serde::de::Visitor<'de> for VariantVisitor {
+                        type Value = #name;
+
+                        fn expecting(
+                            &self,
+                            f: &mut ::core::fmt::Formatter,
+                        ) -> ::core::fmt::Result {
+                            f.write_str(#expecting)
+                        }
+
+                        fn visit_u64<E>(self, value: u64) -> Result<#name, E>
+                        where
+                            E: ::serde::de::Error,
+                        {
+                            match value {
+                                #(#indices_u64 => Ok(#name::#idents),)*
+                                _ => Err(E::invalid_value(
+                                    ::serde::de::Unexpected::Unsigned(value),
+                                    &self,
+                                )),
+                            }
+                        }
+
+                        fn visit_str<E>(self, value: &str) -> Result<#name, E>
+                        where
+                            E: ::serde::de::Error,
+                        {
+                            match value {
+                                #(#names => Ok(#name::#idents),)*
+                                _ => Err(E::unknown_variant(value, VARIANTS)),
+                            }
+                        }
+                    }
+
+                    struct VariantIdent(#name);
+
+                    impl<'de> ::serde::Deserialize<'de> for VariantIdent {
+                        fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+                        where
+                            D: ::serde::Deserializer<'de>,
+                        {
+                            deserializer
+                                .deserialize_identifier(VariantVisitor)
+                                .map(VariantIdent)
+                        }
+                    }
+
+                    struct EnumVisitor;
+
+                    impl<'de> ::serde::de::Visitor<'de> for EnumVisitor {
+                        type Value = #name;
+
+                        fn expecting(
+                            &self,
+                            f: &mut ::core::fmt::Formatter,
+                        ) -> ::core::fmt::Result {
+                            f.write_str(#expecting)
+                        }
+
+                        fn visit_enum<A>(self, data: A) -> Result<#name, A::Error>
+                        where
+                            A: ::serde::de::EnumAccess<'de>,
+                        {
+                            let (VariantIdent(value), variant) = data.variant()?;
+                            ::serde::de::VariantAccess::unit_variant(variant)?;
+                            Ok(value)
+                        }
+                    }
+
+                    deserializer.deserialize_enum(#name_str, VARIANTS, EnumVisitor)
+                }
+            }
+        };
+    }
+}
@@ -0,0 +1,136 @@
+//! Filling areas of a 128x64 `GraphicsMode` a page byte at a time, against
+//! drawing the same pixels one by one with `draw_iter`.
+//!
+//! Run with `cargo bench -p oled_i2c_driver`.
+
+use std::hint::black_box;
+
+use criterion::{criterion_group, criterion_main, Criterion};
+use display_interface::{
+    AsyncWriteOnlyDataCommand,
+    DataFormat,
+    DisplayError,
+    WriteOnlyDataCommand,
+};
+use embedded_graphics_core::{
+    draw_target::DrawTarget,
+    geometry::{Dimensions, Point, Size},
+    pixelcolor::BinaryColor,
+    primitives::{PointsIter, Rectangle},
+    Pixel,
+};
+use oled_i2c_driver::{
+    displays::{ssd1306::Ssd1306, Screen128x64},
+    mode::GraphicsMode,
+    Builder,
+};
+
+/// Interface dropping everything, the benchmarks never flush
+struct NullInterface;
+
+impl WriteOnlyDataCommand for NullInterface {
+    fn send_commands(
+        &mut self,
+        _cmd: DataFormat<'_>,
+    ) -> Result<(), DisplayError> {
+        Ok(())
+    }
+
+    fn send_data(&mut self, _buf: DataFormat<'_>) -> Result<(), DisplayError> {
+        Ok(())
+    }
+}
+
+impl AsyncWriteOnlyDataCommand for NullInterface {
+    async fn send_commands(
+        &mut self,
+        _cmd: DataFormat<'_>,
+    ) -> Result<(), DisplayError> {
+        Ok(())
+    }
+
+    async fn send_data(
+        &mut self,
+        _buf: DataFormat<'_>,
+    ) -> Result<(), DisplayError> {
+        Ok(())
+    }
+}
+
+type Display = GraphicsMode<Ssd1306<Screen128x64>, NullInterface>;
+
+fn display() -> Display {
+    Builder::new(Ssd1306::<Screen128x64>::new())
+        .connect(NullInterface)
+        .into()
+}
+
+/// Rectangle not aligned to pages, as drawn by e.g. a progress bar
+const AREA: Rectangle = Rectangle::new(Point::new(5, 3), Size::new(100, 50));
+
+/// Colors of a 1 bit image covering `AREA`
+fn image() -> impl Iterator<Item = BinaryColor> {
+    (0..).map(|i: u32| BinaryColor::from(i.is_multiple_of(3)))
+}
+
+fn fill_solid(c: &mut Criterion) {
+    let mut group = c.benchmark_group("fill_solid");
+    let mut display = display();
+
+    group.bench_function("pixels", |b| {
+        b.iter(|| {
+            display.draw_iter(
+                black_box(AREA)
+                    .points()
+                    .map(|point| Pixel(point, BinaryColor::On)),
+            )
+        })
+    });
+    group.bench_function("bytes", |b| {
+        b.iter(|| display.fill_solid(&black_box(AREA), BinaryColor::On))
+    });
+    group.finish();
+}
+
+fn fill_contiguous(c: &mut Criterion) {
+    let mut group = c.benchmark_group("fill_contiguous");
+    let mut display = display();
+
+    group.bench_function("pixels", |b| {
+        b.iter(|| {
+            display.draw_iter(
+                black_box(AREA)
+                    .points()
+                    .zip(image())
+                    .map(|(point, color)| Pixel(point, color)),
+            )
+        })
+    });
+    group.bench_function("bytes", |b| {
+        b.iter(|| display.fill_contiguous(&black_box(AREA), image()))
+    });
+    group.finish();
+}
+
+fn clear(c: &mut Criterion) {
+    let mut group = c.benchmark_group("clear");
+    let mut display = display();
+    let screen = display.bounding_box();
+
+    group.bench_function("pixels", |b| {
+        b.iter(|| {
+            display.draw_iter(
+                black_box(screen)
+                    .points()
+                    .map(|point| Pixel(point, BinaryColor::On)),
+            )
+        })
+    });
+    group.bench_function("bytes", |b| {
+        b.iter(|| DrawTarget::clear(&mut display, black_box(BinaryColor::On)))
+    });
+    group.finish();
+}
+
+criterion_group!(benches, fill_solid, fill_contiguous, clear);
+criterion_main!(benches);
@@ -10,17 +10,50 @@
 //! # Examples
 //!
 //! Connect 180 deg rotation to a 128x128 SH1107 based display:
+#![cfg_attr(feature = "async", doc = "```rust,no_run")]
+#![cfg_attr(not(feature = "async"), doc = "```rust,ignore")]
+//! # use display_interface::AsyncWriteOnlyDataCommand;
+//! use oled_i2c_driver::{
+//!     displayrotation::DisplayRotation,
+//!     displays::{sh1107::Sh1107, Screen128x128},
+//!     Builder,
+//! };
 //!
-//! ```rust,no_run
-//! use oled_async::{mode::GraphicsMode, Builder};
-//! let spi = /* Create an SPI 'device' that implements embedded_hal::SpiDevice  using a HAL of your choice */
-//! let di = /*  Use spi to create an interface that implements display_interface::AsyncWriteOnlyDataCommand using a bus that matches your hardware such as display_interface_spi::SPIInterface */
-//!
-//! let mut raw_display = Builder::new(oled_async::displays::sh1107::Sh1107_128_128 {})
-//!         .with_rotation(crate::DisplayRotation::Rotate180)
-//!         .connect(di);
+//! # fn connect(di: impl AsyncWriteOnlyDataCommand) {
+//! // `di` implements display_interface::AsyncWriteOnlyDataCommand, e.g. an
+//! // I2CInterface from display-interface-i2c
+//! let raw_display = Builder::new(Sh1107::<Screen128x128>::new())
+//!     .with_rotation(DisplayRotation::Rotate180)
+//!     .connect(di);
+//! # }
 //! ```
+//! 
+//! Displays with a reset line or a switched VCC supply get their pins and a
+//! delay here too, so `init()` can run the whole power-up sequence:
+#![cfg_attr(feature = "async", doc = "```rust,no_run")]
+#![cfg_attr(not(feature = "async"), doc = "```rust,ignore")]
+//! # use display_interface::AsyncWriteOnlyDataCommand;
+//! # use embedded_hal::digital::OutputPin;
+//! # use embedded_hal_async::delay::DelayNs;
+//! use oled_i2c_driver::{
+//!     displays::{sh1107::Sh1107, Screen128x128},
+//!     Builder,
+//! };
 //!
+//! # fn connect(
+//! #     di: impl AsyncWriteOnlyDataCommand,
+//! #     reset: impl OutputPin,
+//! #     vcc_enable: impl OutputPin,
+//! #     delay: impl DelayNs,
+//! # ) {
+//! let raw_display = Builder::new(Sh1107::<Screen128x128>::new())
+//!     .with_delay(delay)
+//!     .with_reset_pin(reset)
+//!     .with_vcc_pin(vcc_enable)
+//!     .connect(di);
+//! # }
+//! ```
+//! 
 //! The driver is intended to support multiple chipsets, at least in the SH11xx
 //! and SSH13xx families. It is intended to be easy to add additional specific
 //! display variants. This can be done by adding to the provided modules in
@@ -31,17 +64,34 @@
 //! instance by default. You need to coerce them into a mode by specifying a
 //! type on assignment. For example, to use [`GraphicsMode`
 //! mode](../mode/graphics/struct.GraphicsMode.html):
+#![cfg_attr(feature = "async", doc = "```rust,no_run")]
+#![cfg_attr(not(feature = "async"), doc = "```rust,ignore")]
+//! # use display_interface::AsyncWriteOnlyDataCommand;
+//! use oled_i2c_driver::{
+//!     displays::{sh1107::Sh1107, Screen128x128},
+//!     mode::GraphicsMode,
+//!     Builder,
+//! };
 //!
-//! ```rust,no_run
-//! use oled_async::{mode::GraphicsMode, Builder};
+//! # fn connect(di: impl AsyncWriteOnlyDataCommand) {
+//! # let raw_display = Builder::new(Sh1107::<Screen128x128>::new()).connect(di);
 //! let mut display: GraphicsMode<_, _> = raw_display.into();
+//! # }
 //! ```
 
-use display_interface::AsyncWriteOnlyDataCommand;
+#[cfg(feature = "async")]
+use display_interface::AsyncWriteOnlyDataCommand as WriteOnlyDataCommand;
+#[cfg(not(feature = "async"))]
+use display_interface::WriteOnlyDataCommand;
+#[cfg(feature = "async")]
+use embedded_hal_async::delay::DelayNs;
+#[cfg(not(feature = "async"))]
+use hal::delay::DelayNs;
+use hal::digital::OutputPin;
 
 use crate::{
     displayrotation::DisplayRotation,
-    displays::DisplayVariant,
+    displays::{DisplayVariant, InitConfig, VcomhLevel},
     mode::{displaymode::DisplayMode, raw::RawMode},
     properties::DisplayProperties,
 };
@@ -50,12 +100,18 @@ use crate::{
 ///
 /// See the [module level documentation](crate::builder) for more details.
 #[derive(Clone, Copy)]
-pub struct Builder<DV>
+pub struct Builder<DV, RST = NoOutputPin, VCC = NoOutputPin, D = NoDelay>
 where
     DV: DisplayVariant,
 {
     variant: DV,
     rotation: DisplayRotation,
+    mirror_horizontal: bool,
+    mirror_vertical: bool,
+    init_config: InitConfig,
+    reset_pin: Option<RST>,
+    vcc_pin: Option<VCC>,
+    delay: D,
 }
 
 impl<DV> Builder<DV>
@@ -68,11 +124,17 @@ where
         Builder::<DV> {
             variant,
             rotation: DisplayRotation::Rotate0,
+            mirror_horizontal: false,
+            mirror_vertical: false,
+            init_config: DV::INIT_CONFIG,
+            reset_pin: None,
+            vcc_pin: None,
+            delay: NoDelay,
         }
     }
 }
 
-impl<DV> Builder<DV>
+impl<DV, RST, VCC, D> Builder<DV, RST, VCC, D>
 where
     DV: DisplayVariant,
 {
@@ -82,54 +144,250 @@ where
         Self { rotation, ..self }
     }
 
+    /// Mirror the image left to right, e.g. for a display viewed through a
+    /// mirror. Applied on top of the rotation. Defaults to no mirroring.
+    pub fn with_mirror_horizontal(self, mirror: bool) -> Self {
+        Self {
+            mirror_horizontal: mirror,
+            ..self
+        }
+    }
+
+    /// Mirror the image top to bottom. Applied on top of the rotation.
+    /// Defaults to no mirroring.
+    pub fn with_mirror_vertical(self, mirror: bool) -> Self {
+        Self {
+            mirror_vertical: mirror,
+            ..self
+        }
+    }
+
+    /// Replace all initialisation parameters at once. Defaults to the
+    /// variant's [`INIT_CONFIG`](DisplayVariant::INIT_CONFIG).
+    pub fn with_init_config(self, init_config: InitConfig) -> Self {
+        Self {
+            init_config,
+            ..self
+        }
+    }
+
+    /// Set the contrast sent during initialisation
+    pub fn with_contrast(mut self, contrast: u8) -> Self {
+        self.init_config.contrast = contrast;
+        self
+    }
+
+    /// Enable or disable the internal charge pump. Disable it for panels
+    /// supplied with an external VCC.
+    pub fn with_charge_pump(mut self, enabled: bool) -> Self {
+        self.init_config.charge_pump = enabled;
+        self
+    }
+
+    /// Set the precharge period phases, in display clocks from 1 to 15
+    pub fn with_precharge(mut self, phase1: u8, phase2: u8) -> Self {
+        self.init_config.precharge = (phase1, phase2);
+        self
+    }
+
+    /// Set the Vcomh deselect level
+    pub fn with_vcomh(mut self, level: VcomhLevel) -> Self {
+        self.init_config.vcomh = level;
+        self
+    }
+
+    /// Set the display clock. `oscillator_frequency` increases with higher
+    /// values, `divide` is the divide ratio - 1. Both range from 0 to 15.
+    pub fn with_clock(mut self, oscillator_frequency: u8, divide: u8) -> Self {
+        self.init_config.oscillator_frequency = oscillator_frequency;
+        self.init_config.clock_divide = divide;
+        self
+    }
+
+    /// Use the alternative (`true`) or sequential (`false`) COM pin
+    /// configuration, depending on how the panel is wired
+    pub fn with_com_pin_alternative(mut self, alternative: bool) -> Self {
+        self.init_config.com_pin_alternative = alternative;
+        self
+    }
+
+    /// Set the vertical shift of the COM outputs
+    pub fn with_display_offset(mut self, offset: u8) -> Self {
+        self.init_config.display_offset = offset;
+        self
+    }
+
+    /// Set the display RAM row shown on the first line
+    pub fn with_start_line(mut self, line: u8) -> Self {
+        self.init_config.start_line = line;
+        self
+    }
+
+    /// Use `reset` to pulse the display's reset line during `init()`. The
+    /// pulse needs a delay set with [`with_delay`](Self::with_delay), `init()`
+    /// does not compile without one.
+    pub fn with_reset_pin<R>(self, reset: R) -> Builder<DV, R, VCC, D>
+    where
+        R: OutputPin,
+    {
+        Builder {
+            variant: self.variant,
+            rotation: self.rotation,
+            mirror_horizontal: self.mirror_horizontal,
+            mirror_vertical: self.mirror_vertical,
+            init_config: self.init_config,
+            reset_pin: Some(reset),
+            vcc_pin: self.vcc_pin,
+            delay: self.delay,
+        }
+    }
+
+    /// Use `vcc` to switch the panel supply (VCC or VBAT) on once the display
+    /// is initialised, and off again in `power_down()`. Waiting for it to
+    /// settle needs a delay set with [`with_delay`](Self::with_delay),
+    /// `init()` does not compile without one.
+    pub fn with_vcc_pin<V>(self, vcc: V) -> Builder<DV, RST, V, D>
+    where
+        V: OutputPin,
+    {
+        Builder {
+            variant: self.variant,
+            rotation: self.rotation,
+            mirror_horizontal: self.mirror_horizontal,
+            mirror_vertical: self.mirror_vertical,
+            init_config: self.init_config,
+            reset_pin: self.reset_pin,
+            vcc_pin: Some(vcc),
+            delay: self.delay,
+        }
+    }
+
+    /// Set the delay used to time the reset pulse, the supply settling and
+    /// any waits in the variant's init sequence. Required with reset or VCC
+    /// pins, without them the init sequence's waits are skipped.
+    pub fn with_delay<E>(self, delay: E) -> Builder<DV, RST, VCC, E>
+    where
+        E: DelayNs,
+    {
+        Builder {
+            variant: self.variant,
+            rotation: self.rotation,
+            mirror_horizontal: self.mirror_horizontal,
+            mirror_vertical: self.mirror_vertical,
+            init_config: self.init_config,
+            reset_pin: self.reset_pin,
+            vcc_pin: self.vcc_pin,
+            delay,
+        }
+    }
+
     /// Finish the builder and use the given interface to communicate with the
     /// display.
-    pub fn connect<DI>(self, interface: DI) -> DisplayMode<RawMode<DV, DI>>
+    pub fn connect<DI>(
+        self,
+        interface: DI,
+    ) -> DisplayMode<RawMode<DV, DI, RST, VCC, D>>
     where
-        DI: AsyncWriteOnlyDataCommand,
+        DI: WriteOnlyDataCommand,
         DV: DisplayVariant,
     {
         let properties =
-            DisplayProperties::new(self.variant, interface, self.rotation);
-        DisplayMode::<RawMode<DV, DI>>::new(properties)
+            DisplayProperties::new(self.variant, interface, self.rotation)
+                .with_mirror(self.mirror_horizontal, self.mirror_vertical)
+                .with_init_config(self.init_config)
+                .with_power(self.reset_pin, self.vcc_pin, self.delay);
+        DisplayMode::<RawMode<DV, DI, RST, VCC, D>>::new(properties)
+    }
+}
+
+/// Placeholder for a pin that isn't connected
+#[derive(Clone, Copy, Debug, Default)]
+pub struct NoOutputPin;
+
+impl NoOutputPin {
+    /// Create a new placeholder pin
+    pub fn new() -> Self {
+        NoOutputPin
+    }
+}
+
+impl OutputPin for NoOutputPin {
+    fn set_low(&mut self) -> Result<(), Self::Error> {
+        Ok(())
+    }
+
+    fn set_high(&mut self) -> Result<(), Self::Error> {
+        Ok(())
     }
 }
 
-// #[derive(Clone, Copy)]
-// pub enum NoOutputPin {}
+impl hal::digital::ErrorType for NoOutputPin {
+    type Error = core::convert::Infallible;
+}
 
-// impl OutputPin for NoOutputPin {
-//     fn set_low(&mut self) -> Result<(), Self::Error> {
-//         Ok(())
-//     }
+/// Stands in for a delay until one is set with [`Builder::with_delay`]. It
+/// only times the power sequence of displays without reset and VCC pins, see
+/// [`PowerDelay`].
+#[derive(Clone, Copy, Debug, Default)]
+pub struct NoDelay;
 
-//     fn set_high(&mut self) -> Result<(), Self::Error> {
-//         Ok(())
-//     }
-// }
+/// Delay timing the power sequences of `init()` and `power_down()`.
+///
+/// Every [`DelayNs`] is one. [`NoDelay`] is one only while neither a reset nor
+/// a VCC pin is connected, as the reset pulse and the VCC settling time can't
+/// be skipped.
+#[diagnostic::on_unimplemented(
+    message = "a reset or VCC pin needs a delay to time the power sequence",
+    note = "set one with `Builder::with_delay`"
+)]
+pub trait PowerDelay<RST, VCC> {
+    /// Delay to wait with
+    fn delay(&mut self) -> impl DelayNs + '_;
+}
 
-// impl hal::digital::ErrorType for NoOutputPin {
-//     type Error = core::convert::Infallible;
-// }
+impl<RST, VCC, D> PowerDelay<RST, VCC> for D
+where
+    D: DelayNs,
+{
+    fn delay(&mut self) -> impl DelayNs + '_ {
+        self
+    }
+}
 
-// #[cfg(test)]
-// mod tests {
-//     use embedded_hal::digital::OutputPin;
+impl PowerDelay<NoOutputPin, NoOutputPin> for NoDelay {
+    fn delay(&mut self) -> impl DelayNs + '_ {
+        Immediate
+    }
+}
+
+/// Delay that returns immediately, given to the variant's init sequence in
+/// place of [`NoDelay`]
+struct Immediate;
+
+#[cfg(not(feature = "async"))]
+impl DelayNs for Immediate {
+    fn delay_ns(&mut self, _ns: u32) {}
+}
 
-//     use super::NoOutputPin;
+#[cfg(feature = "async")]
+impl DelayNs for Immediate {
+    async fn delay_ns(&mut self, _ns: u32) {}
+}
 
-//     enum SomeError {}
+#[cfg(test)]
+mod tests {
+    use embedded_hal::digital::OutputPin;
 
-//     struct SomeDriver<P: OutputPin<Error = SomeError>> {
-//         #[allow(dead_code)]
-//         p: P,
-//     }
+    use super::NoOutputPin;
 
-//     #[test]
-//     fn test_output_pin() {
-//         let p = NoOutputPin::new();
-//         let _d = SomeDriver { p };
+    struct SomeDriver<P: OutputPin> {
+        #[allow(dead_code)]
+        p: P,
+    }
 
-//         assert!(true);
-//     }
-// }
+    #[test]
+    fn test_output_pin() {
+        let p = NoOutputPin::new();
+        let _d = SomeDriver { p };
+    }
+}
@@ -1,7 +1,49 @@
-use display_interface::{AsyncWriteOnlyDataCommand, DataFormat, DisplayError};
+//! Controller commands
+//!
+//! [`Command`] covers the SH110x/SSD130x command set. Commands it doesn't
+//! cover can be sent with [`send_raw`], several commands can be sent in one
+//! bus transaction with a [`CommandBatch`], and display variants defined out of
+//! tree can describe their power-up as a list of [`InitStep`]s:
+//!
+//! ```rust,no_run
+//! use oled_i2c_driver::{
+//!     command::{Command, InitStep},
+//!     displays::{DisplayVariant, MonochromeVariant},
+//! };
+//!
+//! struct MyPanel;
+//!
+//! impl DisplayVariant for MyPanel {
+//!     const WIDTH: u16 = 128;
+//!     const HEIGHT: u16 = 64;
+//!     type Buffer = [u8; 128 * 64 / 8];
+//! }
+//!
+//! impl MonochromeVariant for MyPanel {
+//!     const INIT_SEQUENCE: &'static [InitStep<'static>] = &[
+//!         InitStep::Command(Command::DisplayOn(false)),
+//!         InitStep::Command(Command::Multiplex(63)),
+//!         InitStep::Raw(&[0x8D, 0x14]),
+//!         InitStep::DelayMs(100),
+//!         InitStep::Command(Command::Contrast(0x80)),
+//!     ];
+//! }
+//! ```
 
-#[derive(Debug)]
-#[allow(dead_code)]
+#[cfg(feature = "async")]
+use display_interface::AsyncWriteOnlyDataCommand as WriteOnlyDataCommand;
+#[cfg(not(feature = "async"))]
+use display_interface::WriteOnlyDataCommand;
+use display_interface::{DataFormat, DisplayError};
+#[cfg(feature = "async")]
+use embedded_hal_async::delay::DelayNs;
+#[cfg(not(feature = "async"))]
+use hal::delay::DelayNs;
+
+use crate::Error;
+
+/// Commands understood by the page addressed controllers
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum Command {
     /// Set the addressing mode.
     /// `false` is page addressing mode.
@@ -28,6 +70,9 @@ pub enum Command {
     LargePageAddress(u8),
     /// Set display start line from 0-63
     StartLine(u8),
+    /// Set display start line (two byte variant for sh1107 and sh1108, which
+    /// have more than 64 RAM rows)
+    LargeStartLine(u8),
     /// Reverse columns from 127-0
     SegmentRemap(bool),
     /// Set multipex ratio from 15-63 (MUX-1)
@@ -55,17 +100,46 @@ pub enum Command {
     Noop,
     /// Enable charge pump
     ChargePump(bool),
+    /// Enable charge pump (SSD1306 form, 0x8D)
+    SsdChargePump(bool),
+    /// Set up a continuous horizontal scroll of the pages from the first to
+    /// the second value, advancing by one column every interval. The last two
+    /// values are the first and last scrolled column on controllers with a
+    /// column window such as the SSD1309, the SSD1306 expects the dummy bytes
+    /// `0x00` and `0xFF` there.
+    HorizontalScrollSetup(ScrollDirection, u8, u8, NFrames, u8, u8),
+    /// Set up a continuous diagonal scroll of the pages from the first to the
+    /// second value. The last value is the vertical offset in rows per step.
+    DiagonalScrollSetup(ScrollDirection, u8, u8, NFrames, u8),
+    /// Set the vertical scroll area as the number of fixed rows at the top
+    /// and the number of scrolled rows below them
+    VerticalScrollArea(u8, u8),
+    /// Start (true) or stop (false) the scroll set up before
+    Scroll(bool),
 }
 
+#[maybe_async_cfg::maybe(
+    sync(cfg(not(feature = "async")),),
+    async(feature = "async"),
+    keep_self
+)]
 impl Command {
-    /// Send command to oled_async
+    /// Send command to the display
     pub async fn send<DI>(self, iface: &mut DI) -> Result<(), DisplayError>
     where
-        DI: AsyncWriteOnlyDataCommand,
+        DI: WriteOnlyDataCommand,
     {
-        // Transform command into a fixed size array of 7 u8 and the real length
-        // for sending
-        let (data, len) = match self {
+        let (data, len) = self.encode();
+        // Send command over the interface
+        iface.send_commands(DataFormat::U8(&data[0..len])).await
+    }
+}
+
+impl Command {
+    /// Transform command into a fixed size array of 7 u8 and the real length
+    /// for sending
+    fn encode(self) -> ([u8; MAX_COMMAND_LEN], usize) {
+        match self {
             Command::AddressMode(mode) => {
                 ([0x20 | (mode as u8), 0, 0, 0, 0, 0, 0], 1)
             }
@@ -91,6 +165,7 @@ impl Command {
             Command::StartLine(line) => {
                 ([0x40 | (0x3F & line), 0, 0, 0, 0, 0, 0], 1)
             }
+            Command::LargeStartLine(line) => ([0xDC, line, 0, 0, 0, 0, 0], 2),
             Command::SegmentRemap(remap) => {
                 ([0xA0 | (remap as u8), 0, 0, 0, 0, 0, 0], 1)
             }
@@ -118,15 +193,243 @@ impl Command {
             Command::ChargePump(en) => {
                 ([0xAD, 0x8A | (en as u8), 0, 0, 0, 0, 0], 2)
             }
-        };
-        // Send command over the interface
-        iface.send_commands(DataFormat::U8(&data[0..len])).await
+            Command::SsdChargePump(en) => {
+                ([0x8D, 0x10 | ((en as u8) << 2), 0, 0, 0, 0, 0], 2)
+            }
+            Command::HorizontalScrollSetup(
+                dir,
+                start,
+                end,
+                rate,
+                first_column,
+                last_column,
+            ) => (
+                [
+                    0x26 | (dir as u8),
+                    0,
+                    0x7 & start,
+                    rate as u8,
+                    0x7 & end,
+                    first_column,
+                    last_column,
+                ],
+                7,
+            ),
+            Command::DiagonalScrollSetup(dir, start, end, rate, offset) => (
+                [
+                    0x29 + (dir as u8),
+                    0,
+                    0x7 & start,
+                    rate as u8,
+                    0x7 & end,
+                    0x3F & offset,
+                    0,
+                ],
+                6,
+            ),
+            Command::VerticalScrollArea(fixed, scrolled) => {
+                ([0xA3, 0x3F & fixed, 0x7F & scrolled, 0, 0, 0, 0], 3)
+            }
+            Command::Scroll(on) => ([0x2E | (on as u8), 0, 0, 0, 0, 0, 0], 1),
+        }
     }
 }
 
-/// Frame interval
+/// Longest encoded [`Command`]
+const MAX_COMMAND_LEN: usize = 7;
+
+/// Bytes a [`CommandBatch`] can hold, enough for any initialisation sequence
+/// of the included variants
+pub const BATCH_CAPACITY: usize = 64;
+
+/// Commands encoded into one buffer and sent with a single `send_commands`
+/// call, so that they share one bus transaction and control byte
+#[cfg_attr(feature = "async", doc = "```rust,no_run")]
+#[cfg_attr(not(feature = "async"), doc = "```rust,ignore")]
+/// # use display_interface::AsyncWriteOnlyDataCommand;
+/// use oled_i2c_driver::{
+///     command::{Command, CommandBatch},
+///     Error,
+/// };
+///
+/// # async fn send(
+/// #     iface: &mut impl AsyncWriteOnlyDataCommand,
+/// # ) -> Result<(), Error> {
+/// CommandBatch::new()
+///     .push(Command::PageAddress(0))
+///     .push(Command::ColumnAddressLow(0))
+///     .push(Command::ColumnAddressHigh(0))
+///     .send(iface)
+///     .await?;
+/// # Ok(())
+/// # }
+/// ```
 #[derive(Debug, Clone, Copy)]
-#[allow(dead_code)]
+pub struct CommandBatch {
+    buffer: [u8; BATCH_CAPACITY],
+    len: usize,
+    overflowed: bool,
+}
+
+impl Default for CommandBatch {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl CommandBatch {
+    /// Create an empty batch
+    pub const fn new() -> Self {
+        CommandBatch {
+            buffer: [0; BATCH_CAPACITY],
+            len: 0,
+            overflowed: false,
+        }
+    }
+
+    /// Append a command
+    pub fn push(self, command: Command) -> Self {
+        let (data, len) = command.encode();
+        self.push_raw(&data[..len])
+    }
+
+    /// Append raw command bytes, for commands not covered by [`Command`]
+    pub fn push_raw(mut self, bytes: &[u8]) -> Self {
+        match self.buffer.get_mut(self.len..self.len + bytes.len()) {
+            Some(free) => {
+                free.copy_from_slice(bytes);
+                self.len += bytes.len();
+            }
+            // Reported when sending, so that pushes can be chained
+            None => self.overflowed = true,
+        }
+        self
+    }
+
+    /// Get the encoded commands
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.buffer[..self.len]
+    }
+
+    /// Get the number of encoded bytes
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Check whether no command was added yet
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Check whether a command of any length still fits
+    fn has_room(&self) -> bool {
+        self.len + MAX_COMMAND_LEN <= BATCH_CAPACITY
+    }
+}
+
+#[maybe_async_cfg::maybe(
+    sync(cfg(not(feature = "async")),),
+    async(feature = "async"),
+    keep_self
+)]
+impl CommandBatch {
+    /// Send all commands in one go. Returns [`Error::OutOfBounds`] without
+    /// sending anything if more than [`BATCH_CAPACITY`] bytes were pushed.
+    pub async fn send<DI>(&self, iface: &mut DI) -> Result<(), Error>
+    where
+        DI: WriteOnlyDataCommand,
+    {
+        if self.overflowed {
+            return Err(Error::OutOfBounds);
+        }
+        if self.is_empty() {
+            return Ok(());
+        }
+
+        iface
+            .send_commands(DataFormat::U8(self.as_bytes()))
+            .await
+            .map_err(Error::Comm)
+    }
+}
+
+/// Send raw command bytes, for commands not covered by [`Command`]
+#[maybe_async_cfg::maybe(
+    sync(cfg(not(feature = "async")),),
+    async(feature = "async"),
+    keep_self
+)]
+pub async fn send_raw<DI>(
+    iface: &mut DI,
+    bytes: &[u8],
+) -> Result<(), DisplayError>
+where
+    DI: WriteOnlyDataCommand,
+{
+    iface.send_commands(DataFormat::U8(bytes)).await
+}
+
+/// One step of a declarative initialisation sequence
+#[derive(Debug, Clone, Copy)]
+pub enum InitStep<'a> {
+    /// Send a command
+    Command(Command),
+    /// Send raw command bytes
+    Raw(&'a [u8]),
+    /// Wait for the given number of milliseconds
+    DelayMs(u32),
+}
+
+/// Run the steps of an initialisation sequence in order
+#[maybe_async_cfg::maybe(
+    sync(cfg(not(feature = "async")),),
+    async(feature = "async"),
+    keep_self
+)]
+pub async fn send_sequence<DI, D>(
+    iface: &mut DI,
+    steps: &[InitStep<'_>],
+    delay: &mut D,
+) -> Result<(), Error>
+where
+    DI: WriteOnlyDataCommand,
+    D: DelayNs,
+{
+    // Consecutive commands share one transaction up to the next delay
+    let mut batch = CommandBatch::new();
+    for step in steps {
+        match *step {
+            InitStep::Command(command) => {
+                if !batch.has_room() {
+                    batch.send(iface).await?;
+                    batch = CommandBatch::new();
+                }
+                batch = batch.push(command);
+            }
+            InitStep::Raw(bytes) => {
+                if batch.len() + bytes.len() > BATCH_CAPACITY {
+                    batch.send(iface).await?;
+                    batch = CommandBatch::new();
+                }
+                if bytes.len() > BATCH_CAPACITY {
+                    send_raw(iface, bytes).await?;
+                } else {
+                    batch = batch.push_raw(bytes);
+                }
+            }
+            InitStep::DelayMs(ms) => {
+                batch.send(iface).await?;
+                batch = CommandBatch::new();
+                delay.delay_ms(ms).await;
+            }
+        }
+    }
+
+    batch.send(iface).await
+}
+
+/// Frame interval
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum NFrames {
     /// 2 Frames
     F2 = 0b111,
@@ -146,9 +449,18 @@ pub enum NFrames {
     F256 = 0b011,
 }
 
+/// Horizontal direction of a continuous scroll, as seen on the unrotated
+/// panel
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ScrollDirection {
+    /// Scroll to the right
+    Right = 0,
+    /// Scroll to the left
+    Left = 1,
+}
+
 /// Vcomh Deselect level
-#[derive(Debug, Clone, Copy)]
-#[allow(dead_code)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum VcomhLevel {
     /// 0.65 * Vcc
     V065 = 0b001,
@@ -159,3 +471,39 @@ pub enum VcomhLevel {
     /// Auto
     Auto = 0b100,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{Command, CommandBatch, BATCH_CAPACITY};
+    use crate::{
+        test_helpers::{block_on, RecordingInterface},
+        Error,
+    };
+
+    #[test]
+    fn batch_concatenates_commands() {
+        let batch = CommandBatch::new()
+            .push(Command::PageAddress(2))
+            .push(Command::Contrast(0x80))
+            .push_raw(&[0xE3]);
+
+        assert_eq!(batch.as_bytes(), &[0xB2, 0x81, 0x80, 0xE3]);
+    }
+
+    #[test]
+    fn batch_rejects_overflow() {
+        let batch = CommandBatch::new()
+            .push_raw(&[0; BATCH_CAPACITY])
+            .push(Command::Noop);
+
+        assert_eq!(batch.len(), BATCH_CAPACITY);
+        assert!(batch.overflowed);
+
+        let mut iface = RecordingInterface::new();
+        assert!(matches!(
+            block_on(batch.send(&mut iface)),
+            Err(Error::OutOfBounds)
+        ));
+        assert!(iface.transfers.is_empty());
+    }
+}
@@ -9,3 +9,36 @@ pub enum DisplayRotation {
     /// Rotate 270 degress clockwise
     Rotate270,
 }
+
+impl DisplayRotation {
+    /// Map the coordinates of a pixel on the rotated display to the unrotated
+    /// panel of size `dimensions`. Returns `None` if the pixel is outside the
+    /// display.
+    pub(crate) fn to_panel(
+        self,
+        x: u32,
+        y: u32,
+        dimensions: (u16, u16),
+    ) -> Option<(u32, u32)> {
+        let (width, height) =
+            (u32::from(dimensions.0), u32::from(dimensions.1));
+        let (rotated_width, rotated_height) = match self {
+            DisplayRotation::Rotate0 | DisplayRotation::Rotate180 => {
+                (width, height)
+            }
+            DisplayRotation::Rotate90 | DisplayRotation::Rotate270 => {
+                (height, width)
+            }
+        };
+        if x >= rotated_width || y >= rotated_height {
+            return None;
+        }
+
+        Some(match self {
+            DisplayRotation::Rotate0 => (x, y),
+            DisplayRotation::Rotate90 => (width - 1 - y, x),
+            DisplayRotation::Rotate180 => (width - 1 - x, height - 1 - y),
+            DisplayRotation::Rotate270 => (y, height - 1 - x),
+        })
+    }
+}
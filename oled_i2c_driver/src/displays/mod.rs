@@ -4,37 +4,226 @@
 //! will behave, and what methods it exposes. Look at the modes below for more
 //! information on what they expose.
 
-use display_interface::{AsyncWriteOnlyDataCommand, DisplayError};
+#[cfg(feature = "async")]
+use display_interface::AsyncWriteOnlyDataCommand as WriteOnlyDataCommand;
+#[cfg(not(feature = "async"))]
+use display_interface::WriteOnlyDataCommand;
+#[cfg(feature = "async")]
+use embedded_hal_async::delay::DelayNs;
+#[cfg(not(feature = "async"))]
+use hal::delay::DelayNs;
+
+pub use crate::command::VcomhLevel;
+use crate::{
+    command::{send_sequence, InitStep},
+    Error,
+};
 
 pub mod sh1106;
 pub mod sh1107;
 pub mod sh1108;
+pub mod sh1122;
+pub mod ssd1306;
 pub mod ssd1309;
+pub mod ssd1322;
+pub mod ssd1327;
+#[cfg(test)]
+mod tests;
 
+/// Properties shared by all supported controllers
 pub trait DisplayVariant {
     /// Width of display
-    const WIDTH: u8;
+    const WIDTH: u16;
     /// Height of display
-    const HEIGHT: u8;
+    const HEIGHT: u16;
+    /// Initialisation parameters used unless overridden in the
+    /// [`Builder`](crate::Builder)
+    const INIT_CONFIG: InitConfig = InitConfig::DEFAULT;
+
+    /// Framebuffer sized for the panel, e.g. `[u8; 128 * 64 / 8]` for one bit
+    /// per pixel. The modes check at compile time that it is large enough.
+    type Buffer: Framebuffer;
+
+    /// Get integral dimensions from DisplaySize
+    fn dimensions() -> (u16, u16) {
+        (Self::WIDTH, Self::HEIGHT)
+    }
+}
+
+/// Display variant with a 1-bit RAM addressed in pages of 8 rows, as on the
+/// SH110x and SSD130x controllers. These are driven through
+/// [`GraphicsMode`](crate::mode::GraphicsMode) and
+/// [`TerminalMode`](crate::mode::TerminalMode).
+#[maybe_async_cfg::maybe(
+    sync(cfg(not(feature = "async")),),
+    async(feature = "async"),
+    keep_self
+)]
+pub trait MonochromeVariant: DisplayVariant {
     /// Coumn offset
     const COLUMN_OFFSET: u8 = 0;
     /// Large Page AddressP
     const LARGE_PAGE_ADDRESS: bool = false;
+    /// Number of RAM rows and COM outputs of the controller, the range of the
+    /// start line and the display offset. Above 64 the two byte
+    /// [`LargeStartLine`](crate::command::Command::LargeStartLine) command is
+    /// used.
+    const START_LINES: u16 = 64;
+    /// Whether the controller has the SSD130x continuous scroll commands
+    const HARDWARE_SCROLL: bool = false;
+    /// Whether the horizontal scroll setup ends with the start and end column
+    /// of the scrolled area, as on the SSD1309. The SSD1306 takes the dummy
+    /// bytes `0x00` and `0xFF` there instead.
+    const SCROLL_COLUMN_WINDOW: bool = false;
+    /// Commands sent by the default
+    /// [`init_column_mode`](MonochromeVariant::init_column_mode). Variants
+    /// either list their power-up here or implement `init_column_mode`
+    /// themselves. The sequence leaves the display off, the driver switches it
+    /// on once VCC is up.
+    const INIT_SEQUENCE: &'static [InitStep<'static>] = &[];
 
-    /// Get integral dimensions from DisplaySize
-    fn dimensions() -> (u8, u8) {
-        (Self::WIDTH, Self::HEIGHT)
+    /// Initialise the display for column mode, leaving it off. Sends
+    /// [`INIT_SEQUENCE`](MonochromeVariant::INIT_SEQUENCE) unless overridden,
+    /// in which case `config` is not applied.
+    #[allow(async_fn_in_trait)]
+    async fn init_column_mode<DI, D>(
+        iface: &mut DI,
+        _config: &InitConfig,
+        delay: &mut D,
+    ) -> Result<(), Error>
+    where
+        DI: WriteOnlyDataCommand,
+        D: DelayNs,
+    {
+        send_sequence(iface, Self::INIT_SEQUENCE, delay).await
     }
+}
+
+/// Panel dependent parameters sent while initialising the display.
+///
+/// Each [`DisplayVariant`] provides defaults in
+/// [`INIT_CONFIG`](DisplayVariant::INIT_CONFIG) that suit the common modules,
+/// panels with e.g. an external VCC can override them through the
+/// [`Builder`](crate::Builder). The grayscale controllers only use the
+/// parameters they have an equivalent command for.
+#[derive(Debug, Clone, Copy)]
+pub struct InitConfig {
+    /// Contrast, higher is brighter
+    pub contrast: u8,
+    /// Enable the internal charge pump. Disable for panels supplied with an
+    /// external VCC.
+    pub charge_pump: bool,
+    /// Precharge period phase 1 and phase 2 in display clocks, each 1-15
+    pub precharge: (u8, u8),
+    /// Vcomh deselect level
+    pub vcomh: VcomhLevel,
+    /// Oscillator frequency, 0-15, increasing with higher value
+    pub oscillator_frequency: u8,
+    /// Display clock divide ratio - 1, 0-15
+    pub clock_divide: u8,
+    /// Alternative (`true`) or sequential (`false`) COM pin configuration,
+    /// depending on how the panel is wired to the controller
+    pub com_pin_alternative: bool,
+    /// Vertical shift of the COM outputs
+    pub display_offset: u8,
+    /// Display RAM row shown on the first line
+    pub start_line: u8,
+}
+
+impl InitConfig {
+    /// Parameters used by the SH110x and SSD130x variants unless they
+    /// override them
+    pub const DEFAULT: Self = InitConfig {
+        contrast: 0x80,
+        charge_pump: true,
+        precharge: (0x1, 0xF),
+        vcomh: VcomhLevel::Auto,
+        oscillator_frequency: 0x8,
+        clock_divide: 0x0,
+        com_pin_alternative: true,
+        display_offset: 0,
+        start_line: 0,
+    };
+}
 
-    /// Initialise the display for column mode
+impl Default for InitConfig {
+    fn default() -> Self {
+        Self::DEFAULT
+    }
+}
+
+/// Byte array holding a display's framebuffer
+pub trait Framebuffer: AsRef<[u8]> + AsMut<[u8]> {
+    /// Length in bytes
+    const LEN: usize;
+    /// Buffer with all pixels off
+    const ZEROED: Self;
+}
+
+impl<const N: usize> Framebuffer for [u8; N] {
+    const LEN: usize = N;
+    const ZEROED: Self = [0; N];
+}
+
+/// Display variant with a 4-bit grayscale RAM addressed by row and column
+/// window instead of pages. Two horizontally adjacent pixels share a byte, the
+/// left one in the high nibble.
+///
+/// Their [`Buffer`](DisplayVariant::Buffer) holds four bits per pixel.
+///
+/// These controllers use a different command set from the page addressed
+/// ones, so they are driven through
+/// [`GrayscaleMode`](crate::mode::GrayscaleMode) only.
+#[maybe_async_cfg::maybe(
+    sync(cfg(not(feature = "async")),),
+    async(feature = "async"),
+    keep_self
+)]
+pub trait GrayscaleVariant: DisplayVariant {
+    /// Whether the RAM write position continues on the next row of the window
+    /// after its last column. If not, the window is set up again for every
+    /// row.
+    const WINDOW_WRAPS: bool = true;
+
+    /// Send the power-up commands, applying the parameters of `config` the
+    /// controller has an equivalent command for. The display is left off.
     #[allow(async_fn_in_trait)]
-    async fn init_column_mode<DI>(iface: &mut DI) -> Result<(), DisplayError>
+    async fn init_grayscale<DI, D>(
+        iface: &mut DI,
+        config: &InitConfig,
+        delay: &mut D,
+    ) -> Result<(), Error>
     where
-        DI: AsyncWriteOnlyDataCommand;
+        DI: WriteOnlyDataCommand,
+        D: DelayNs;
+
+    /// Set the RAM window for the following data writes. `columns` and
+    /// `rows` are pixel ranges with exclusive end; columns have to be aligned
+    /// to the controller's column address unit.
+    #[allow(async_fn_in_trait)]
+    async fn set_window<DI>(
+        iface: &mut DI,
+        columns: (u16, u16),
+        rows: (u16, u16),
+    ) -> Result<(), Error>
+    where
+        DI: WriteOnlyDataCommand;
 }
 
 pub trait ScreenSize {}
 
+pub struct Screen64x48;
+impl ScreenSize for Screen64x48 {}
+
+pub struct Screen72x40;
+impl ScreenSize for Screen72x40 {}
+
+pub struct Screen96x16;
+impl ScreenSize for Screen96x16 {}
+
+pub struct Screen128x32;
+impl ScreenSize for Screen128x32 {}
+
 pub struct Screen128x64;
 impl ScreenSize for Screen128x64 {}
 
@@ -55,3 +244,6 @@ impl ScreenSize for Screen128x160 {}
 
 pub struct Screen160x160;
 impl ScreenSize for Screen160x160 {}
+
+pub struct Screen256x64;
+impl ScreenSize for Screen256x64 {}
@@ -1,62 +1,105 @@
 use core::marker::PhantomData;
 
-use display_interface::{AsyncWriteOnlyDataCommand, DisplayError};
+#[cfg(feature = "async")]
+use display_interface::AsyncWriteOnlyDataCommand as WriteOnlyDataCommand;
+#[cfg(not(feature = "async"))]
+use display_interface::WriteOnlyDataCommand;
+#[cfg(feature = "async")]
+use embedded_hal_async::delay::DelayNs;
+#[cfg(not(feature = "async"))]
+use hal::delay::DelayNs;
 
-use super::{DisplayVariant, Screen128x64};
-use crate::command::{Command, VcomhLevel};
+use super::{DisplayVariant, InitConfig, MonochromeVariant, Screen128x64};
+use crate::{
+    command::{Command, CommandBatch},
+    Error,
+};
 
 #[derive(Debug, Clone, Copy)]
 pub struct Sh1106<SS> {
     _ss: PhantomData<SS>,
 }
 
+impl<SS> Sh1106<SS> {
+    /// Create the variant, e.g. `Sh1106::<Screen128x64>::new()`
+    pub const fn new() -> Self {
+        Sh1106 { _ss: PhantomData }
+    }
+}
+
+impl<SS> Default for Sh1106<SS> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl DisplayVariant for Sh1106<Screen128x64> {
-    const WIDTH: u8 = 128;
-    const HEIGHT: u8 = 64;
+    const WIDTH: u16 = 128;
+    const HEIGHT: u16 = 64;
+    type Buffer = [u8; 128 * 64 / 8];
+}
+
+#[maybe_async_cfg::maybe(
+    sync(cfg(not(feature = "async")),),
+    async(feature = "async"),
+    keep_self
+)]
+impl MonochromeVariant for Sh1106<Screen128x64> {
     const COLUMN_OFFSET: u8 = 2;
 
-    async fn init_column_mode<DI>(iface: &mut DI) -> Result<(), DisplayError>
+    async fn init_column_mode<DI, D>(
+        iface: &mut DI,
+        config: &InitConfig,
+        _delay: &mut D,
+    ) -> Result<(), Error>
     where
-        DI: AsyncWriteOnlyDataCommand,
+        DI: WriteOnlyDataCommand,
+        D: DelayNs,
     {
-        init_column_mode_common(iface, Self::dimensions()).await?;
-        Command::DisplayOffset(0).send(iface).await?;
-        Command::ComPinConfig(true).send(iface).await?;
-
-        Ok(())
+        init_column_mode_common(iface, Self::dimensions(), config).await
     }
 }
 
 /// Initialise the display in column mode (i.e. a byte walks down a column of 8
 /// pixels) with column 0 on the left and column _(display_width - 1)_ on the
 /// right.
+#[maybe_async_cfg::maybe(
+    sync(cfg(not(feature = "async")),),
+    async(feature = "async"),
+    keep_self
+)]
 async fn init_column_mode_common<DI>(
     iface: &mut DI,
-    dimensions: (u8, u8),
-) -> Result<(), DisplayError>
+    dimensions: (u16, u16),
+    config: &InitConfig,
+) -> Result<(), Error>
 where
-    DI: AsyncWriteOnlyDataCommand,
+    DI: WriteOnlyDataCommand,
 {
-    //iface.init().await?;
-    // TODO: Break up into nice bits so display modes can pick whathever they
-    // need
     let (_, display_height) = dimensions;
 
-    Command::DisplayOn(false).send(iface).await?;
-    Command::DisplayClockDiv(0x8, 0x0).send(iface).await?;
-    Command::Multiplex(display_height - 1).send(iface).await?;
-
-    Command::StartLine(0).send(iface).await?;
-    // TODO: Ability to turn charge pump on/off
-    // Display must be off when performing this command
-    Command::ChargePump(true).send(iface).await?;
-
-    Command::Contrast(0x80).send(iface).await?;
-    Command::PreChargePeriod(0x1, 0xF).send(iface).await?;
-    Command::VcomhDeselect(VcomhLevel::Auto).send(iface).await?;
-    Command::AllOn(false).send(iface).await?;
-    Command::Invert(false).send(iface).await?;
-    Command::DisplayOn(true).send(iface).await?;
+    CommandBatch::new()
+        .push(Command::DisplayOn(false))
+        .push(Command::DisplayClockDiv(
+            config.oscillator_frequency,
+            config.clock_divide,
+        ))
+        .push(Command::Multiplex((display_height - 1) as u8))
+        .push(Command::DisplayOffset(config.display_offset))
+        .push(Command::StartLine(config.start_line))
+        .push(Command::ComPinConfig(config.com_pin_alternative))
+        // Display must be off when performing this command
+        .push(Command::ChargePump(config.charge_pump))
+        .push(Command::Contrast(config.contrast))
+        .push(Command::PreChargePeriod(
+            config.precharge.0,
+            config.precharge.1,
+        ))
+        .push(Command::VcomhDeselect(config.vcomh))
+        .push(Command::AllOn(false))
+        .push(Command::Invert(false))
+        .send(iface)
+        .await?;
 
     Ok(())
 }
@@ -1,15 +1,33 @@
 use core::marker::PhantomData;
 
-use display_interface::{AsyncWriteOnlyDataCommand, DisplayError};
+#[cfg(feature = "async")]
+use display_interface::AsyncWriteOnlyDataCommand as WriteOnlyDataCommand;
+#[cfg(not(feature = "async"))]
+use display_interface::WriteOnlyDataCommand;
+#[cfg(feature = "async")]
+use embedded_hal_async::delay::DelayNs;
+#[cfg(not(feature = "async"))]
+use hal::delay::DelayNs;
 
 use super::{
     DisplayVariant,
+    InitConfig,
+    MonochromeVariant,
     Screen128x160,
     Screen160x160,
     Screen64x160,
     Screen96x160,
 };
-use crate::command::{Command, VcomhLevel};
+use crate::{
+    command::{Command, CommandBatch},
+    Error,
+};
+
+/// The SH1108 runs from a slower oscillator setting than the SH1106/SH1107
+const SH1108_INIT_CONFIG: InitConfig = InitConfig {
+    oscillator_frequency: 0x6,
+    ..InitConfig::DEFAULT
+};
 
 /// Generic 64x160 with SH1108 controller
 #[derive(Debug, Clone, Copy)]
@@ -17,116 +35,182 @@ pub struct Sh1108<SS> {
     _ss: PhantomData<SS>,
 }
 
+impl<SS> Sh1108<SS> {
+    /// Create the variant, e.g. `Sh1108::<Screen64x160>::new()`
+    pub const fn new() -> Self {
+        Sh1108 { _ss: PhantomData }
+    }
+}
+
+impl<SS> Default for Sh1108<SS> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl DisplayVariant for Sh1108<Screen64x160> {
-    const WIDTH: u8 = 64;
-    const HEIGHT: u8 = 160;
+    const WIDTH: u16 = 64;
+    const HEIGHT: u16 = 160;
+    type Buffer = [u8; 64 * 160 / 8];
+    const INIT_CONFIG: InitConfig = SH1108_INIT_CONFIG;
+}
+
+#[maybe_async_cfg::maybe(
+    sync(cfg(not(feature = "async")),),
+    async(feature = "async"),
+    keep_self
+)]
+impl MonochromeVariant for Sh1108<Screen64x160> {
     const COLUMN_OFFSET: u8 = 48;
     const LARGE_PAGE_ADDRESS: bool = true;
+    const START_LINES: u16 = 160;
 
-    async fn init_column_mode<DI>(iface: &mut DI) -> Result<(), DisplayError>
+    async fn init_column_mode<DI, D>(
+        iface: &mut DI,
+        config: &InitConfig,
+        _delay: &mut D,
+    ) -> Result<(), Error>
     where
-        DI: AsyncWriteOnlyDataCommand,
+        DI: WriteOnlyDataCommand,
+        D: DelayNs,
     {
-        init_column_mode_common(iface, Self::dimensions(), 0).await?;
-        Command::DisplayOffset(0).send(iface).await?;
-        Command::ComPinConfig(true).send(iface).await?;
-
-        Ok(())
+        init_column_mode_common(iface, Self::dimensions(), 0, config).await
     }
 }
 
 impl DisplayVariant for Sh1108<Screen96x160> {
-    const WIDTH: u8 = 96;
-    const HEIGHT: u8 = 160;
+    const WIDTH: u16 = 96;
+    const HEIGHT: u16 = 160;
+    type Buffer = [u8; 96 * 160 / 8];
+    const INIT_CONFIG: InitConfig = SH1108_INIT_CONFIG;
+}
+
+#[maybe_async_cfg::maybe(
+    sync(cfg(not(feature = "async")),),
+    async(feature = "async"),
+    keep_self
+)]
+impl MonochromeVariant for Sh1108<Screen96x160> {
     const COLUMN_OFFSET: u8 = 32;
     const LARGE_PAGE_ADDRESS: bool = true;
+    const START_LINES: u16 = 160;
 
-    async fn init_column_mode<DI>(iface: &mut DI) -> Result<(), DisplayError>
+    async fn init_column_mode<DI, D>(
+        iface: &mut DI,
+        config: &InitConfig,
+        _delay: &mut D,
+    ) -> Result<(), Error>
     where
-        DI: AsyncWriteOnlyDataCommand,
+        DI: WriteOnlyDataCommand,
+        D: DelayNs,
     {
-        init_column_mode_common(iface, Self::dimensions(), 1).await?;
-        Command::DisplayOffset(0).send(iface).await?;
-        Command::ComPinConfig(true).send(iface).await?;
-
-        Ok(())
+        init_column_mode_common(iface, Self::dimensions(), 1, config).await
     }
 }
 
 impl DisplayVariant for Sh1108<Screen128x160> {
-    const WIDTH: u8 = 128;
-    const HEIGHT: u8 = 160;
+    const WIDTH: u16 = 128;
+    const HEIGHT: u16 = 160;
+    type Buffer = [u8; 128 * 160 / 8];
+    const INIT_CONFIG: InitConfig = SH1108_INIT_CONFIG;
+}
+
+#[maybe_async_cfg::maybe(
+    sync(cfg(not(feature = "async")),),
+    async(feature = "async"),
+    keep_self
+)]
+impl MonochromeVariant for Sh1108<Screen128x160> {
     const COLUMN_OFFSET: u8 = 16;
     const LARGE_PAGE_ADDRESS: bool = true;
+    const START_LINES: u16 = 160;
 
-    async fn init_column_mode<DI>(iface: &mut DI) -> Result<(), DisplayError>
+    async fn init_column_mode<DI, D>(
+        iface: &mut DI,
+        config: &InitConfig,
+        _delay: &mut D,
+    ) -> Result<(), Error>
     where
-        DI: AsyncWriteOnlyDataCommand,
+        DI: WriteOnlyDataCommand,
+        D: DelayNs,
     {
-        init_column_mode_common(iface, Self::dimensions(), 2).await?;
-        Command::DisplayOffset(0).send(iface).await?;
-        Command::ComPinConfig(true).send(iface).await?;
-
-        Ok(())
+        init_column_mode_common(iface, Self::dimensions(), 2, config).await
     }
 }
 
 impl DisplayVariant for Sh1108<Screen160x160> {
-    const WIDTH: u8 = 160;
-    const HEIGHT: u8 = 160;
+    const WIDTH: u16 = 160;
+    const HEIGHT: u16 = 160;
+    type Buffer = [u8; 160 * 160 / 8];
+    const INIT_CONFIG: InitConfig = SH1108_INIT_CONFIG;
+}
+
+#[maybe_async_cfg::maybe(
+    sync(cfg(not(feature = "async")),),
+    async(feature = "async"),
+    keep_self
+)]
+impl MonochromeVariant for Sh1108<Screen160x160> {
     const COLUMN_OFFSET: u8 = 0;
     const LARGE_PAGE_ADDRESS: bool = true;
+    const START_LINES: u16 = 160;
 
-    async fn init_column_mode<DI>(
+    async fn init_column_mode<DI, D>(
         iface: &mut DI,
-        //display_rotation: DisplayRotation,
-    ) -> Result<(), DisplayError>
+        config: &InitConfig,
+        _delay: &mut D,
+    ) -> Result<(), Error>
     where
-        DI: AsyncWriteOnlyDataCommand,
+        DI: WriteOnlyDataCommand,
+        D: DelayNs,
     {
-        init_column_mode_common(iface, Self::dimensions(), 3).await?;
-        Command::DisplayOffset(0).send(iface).await?;
-        Command::ComPinConfig(true).send(iface).await?;
-
-        Ok(())
+        init_column_mode_common(iface, Self::dimensions(), 3, config).await
     }
 }
 
 /// Initialise the display in column mode (i.e. a byte walks down a column of 8
 /// pixels) with column 0 on the left and column _(display_width - 1)_ on the
 /// right.
+#[maybe_async_cfg::maybe(
+    sync(cfg(not(feature = "async")),),
+    async(feature = "async"),
+    keep_self
+)]
 async fn init_column_mode_common<DI>(
     iface: &mut DI,
-    dimensions: (u8, u8),
+    dimensions: (u16, u16),
     resolution: u8,
-) -> Result<(), DisplayError>
+    config: &InitConfig,
+) -> Result<(), Error>
 where
-    DI: AsyncWriteOnlyDataCommand,
+    DI: WriteOnlyDataCommand,
 {
-    //iface.init().await?;
-    // TODO: Break up into nice bits so display modes can pick whathever they
-    // need
     let (_, display_height) = dimensions;
 
-    Command::DisplayOn(false).send(iface).await?;
-    Command::DisplayClockDiv(0x6, 0x0).send(iface).await?;
-    Command::DisplayResolution(resolution).send(iface).await?;
-    Command::PreChargePeriod(0x8, 0x2).send(iface).await?;
-    Command::DisplayOn(true).send(iface).await?;
-
-    Command::Multiplex(display_height - 1).send(iface).await?;
-
-    Command::StartLine(0).send(iface).await?;
-    // TODO: Ability to turn charge pump on/off
-    // Display must be off when performing this command
-    Command::ChargePump(true).send(iface).await?;
-
-    Command::Contrast(0x80).send(iface).await?;
-    Command::PreChargePeriod(0x1, 0xF).send(iface).await?;
-    Command::VcomhDeselect(VcomhLevel::Auto).send(iface).await?;
-    Command::AllOn(false).send(iface).await?;
-    Command::Invert(false).send(iface).await?;
-    Command::DisplayOn(true).send(iface).await?;
+    CommandBatch::new()
+        .push(Command::DisplayOn(false))
+        .push(Command::DisplayClockDiv(
+            config.oscillator_frequency,
+            config.clock_divide,
+        ))
+        .push(Command::DisplayResolution(resolution))
+        .push(Command::PreChargePeriod(0x8, 0x2))
+        .push(Command::Multiplex((display_height - 1) as u8))
+        .push(Command::DisplayOffset(config.display_offset))
+        .push(Command::LargeStartLine(config.start_line))
+        .push(Command::ComPinConfig(config.com_pin_alternative))
+        // Display must be off when performing this command
+        .push(Command::ChargePump(config.charge_pump))
+        .push(Command::Contrast(config.contrast))
+        .push(Command::PreChargePeriod(
+            config.precharge.0,
+            config.precharge.1,
+        ))
+        .push(Command::VcomhDeselect(config.vcomh))
+        .push(Command::AllOn(false))
+        .push(Command::Invert(false))
+        .send(iface)
+        .await?;
 
     Ok(())
 }
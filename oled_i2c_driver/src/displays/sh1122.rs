@@ -0,0 +1,114 @@
+use core::marker::PhantomData;
+
+#[cfg(feature = "async")]
+use display_interface::AsyncWriteOnlyDataCommand as WriteOnlyDataCommand;
+#[cfg(not(feature = "async"))]
+use display_interface::WriteOnlyDataCommand;
+#[cfg(feature = "async")]
+use embedded_hal_async::delay::DelayNs;
+#[cfg(not(feature = "async"))]
+use hal::delay::DelayNs;
+
+use super::{DisplayVariant, GrayscaleVariant, InitConfig, Screen256x64};
+use crate::{
+    command::{Command, CommandBatch},
+    Error,
+};
+
+/// Generic SH1122 based 4-bit grayscale display
+#[derive(Debug, Clone, Copy)]
+pub struct Sh1122<SS> {
+    _ss: PhantomData<SS>,
+}
+
+impl<SS> Sh1122<SS> {
+    /// Create the variant, e.g. `Sh1122::<Screen256x64>::new()`
+    pub const fn new() -> Self {
+        Sh1122 { _ss: PhantomData }
+    }
+}
+
+impl<SS> Default for Sh1122<SS> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Pixels per column address
+const PIXELS_PER_COLUMN: u16 = 2;
+
+impl DisplayVariant for Sh1122<Screen256x64> {
+    const WIDTH: u16 = 256;
+    const HEIGHT: u16 = 64;
+    type Buffer = [u8; 256 * 64 / 2];
+    const INIT_CONFIG: InitConfig = InitConfig {
+        precharge: (0x2, 0x2),
+        oscillator_frequency: 0x5,
+        ..InitConfig::DEFAULT
+    };
+}
+
+#[maybe_async_cfg::maybe(
+    sync(cfg(not(feature = "async")),),
+    async(feature = "async"),
+    keep_self
+)]
+impl GrayscaleVariant for Sh1122<Screen256x64> {
+    // The row address only advances on a write past the last RAM column
+    const WINDOW_WRAPS: bool = false;
+
+    async fn init_grayscale<DI, D>(
+        iface: &mut DI,
+        config: &InitConfig,
+        _delay: &mut D,
+    ) -> Result<(), Error>
+    where
+        DI: WriteOnlyDataCommand,
+        D: DelayNs,
+    {
+        let (_, display_height) = Self::dimensions();
+
+        // The charge pump, VCOMH and COM pin settings are left at their reset
+        // values
+        CommandBatch::new()
+            .push(Command::DisplayOn(false))
+            .push(Command::DisplayClockDiv(
+                config.oscillator_frequency,
+                config.clock_divide,
+            ))
+            .push(Command::Multiplex((display_height - 1) as u8))
+            .push(Command::DisplayOffset(config.display_offset))
+            .push(Command::StartLine(config.start_line))
+            .push(Command::SegmentRemap(false))
+            .push(Command::ReverseComDir(false))
+            .push(Command::Contrast(config.contrast))
+            .push(Command::PreChargePeriod(
+                config.precharge.0,
+                config.precharge.1,
+            ))
+            .push(Command::AllOn(false))
+            .push(Command::Invert(false))
+            .send(iface)
+            .await?;
+
+        Ok(())
+    }
+
+    async fn set_window<DI>(
+        iface: &mut DI,
+        columns: (u16, u16),
+        rows: (u16, u16),
+    ) -> Result<(), Error>
+    where
+        DI: WriteOnlyDataCommand,
+    {
+        let column = (columns.0 / PIXELS_PER_COLUMN) as u8;
+        // The row address takes a parameter byte like the SH1108 page address
+        CommandBatch::new()
+            .push(Command::LargePageAddress(rows.0 as u8))
+            .push(Command::ColumnAddressLow(0xF & column))
+            .push(Command::ColumnAddressHigh(0xF & (column >> 4)))
+            .send(iface)
+            .await
+    }
+}
@@ -0,0 +1,235 @@
+use core::marker::PhantomData;
+
+#[cfg(feature = "async")]
+use display_interface::AsyncWriteOnlyDataCommand as WriteOnlyDataCommand;
+#[cfg(not(feature = "async"))]
+use display_interface::WriteOnlyDataCommand;
+#[cfg(feature = "async")]
+use embedded_hal_async::delay::DelayNs;
+#[cfg(not(feature = "async"))]
+use hal::delay::DelayNs;
+
+use super::{
+    DisplayVariant,
+    InitConfig,
+    MonochromeVariant,
+    Screen128x32,
+    Screen128x64,
+    Screen64x48,
+    Screen72x40,
+    Screen96x16,
+};
+use crate::{
+    command::{Command, CommandBatch},
+    Error,
+};
+
+/// Modules with 32 rows or fewer wire the COM pins sequentially
+const SEQUENTIAL_COM_INIT_CONFIG: InitConfig = InitConfig {
+    com_pin_alternative: false,
+    ..InitConfig::DEFAULT
+};
+
+/// Generic SSD1306 based display. The controller drives 128 columns; panels
+/// narrower than that are wired to the middle of the column range.
+#[derive(Debug, Clone, Copy)]
+pub struct Ssd1306<SS> {
+    _ss: PhantomData<SS>,
+}
+
+impl<SS> Ssd1306<SS> {
+    /// Create the variant, e.g. `Ssd1306::<Screen128x64>::new()`
+    pub const fn new() -> Self {
+        Ssd1306 { _ss: PhantomData }
+    }
+}
+
+impl<SS> Default for Ssd1306<SS> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl DisplayVariant for Ssd1306<Screen128x64> {
+    const WIDTH: u16 = 128;
+    const HEIGHT: u16 = 64;
+    type Buffer = [u8; 128 * 64 / 8];
+}
+
+#[maybe_async_cfg::maybe(
+    sync(cfg(not(feature = "async")),),
+    async(feature = "async"),
+    keep_self
+)]
+impl MonochromeVariant for Ssd1306<Screen128x64> {
+    const HARDWARE_SCROLL: bool = true;
+
+    async fn init_column_mode<DI, D>(
+        iface: &mut DI,
+        config: &InitConfig,
+        _delay: &mut D,
+    ) -> Result<(), Error>
+    where
+        DI: WriteOnlyDataCommand,
+        D: DelayNs,
+    {
+        init_column_mode_common(iface, Self::dimensions(), config).await
+    }
+}
+
+impl DisplayVariant for Ssd1306<Screen128x32> {
+    const WIDTH: u16 = 128;
+    const HEIGHT: u16 = 32;
+    type Buffer = [u8; 128 * 32 / 8];
+    const INIT_CONFIG: InitConfig = SEQUENTIAL_COM_INIT_CONFIG;
+}
+
+#[maybe_async_cfg::maybe(
+    sync(cfg(not(feature = "async")),),
+    async(feature = "async"),
+    keep_self
+)]
+impl MonochromeVariant for Ssd1306<Screen128x32> {
+    const HARDWARE_SCROLL: bool = true;
+
+    async fn init_column_mode<DI, D>(
+        iface: &mut DI,
+        config: &InitConfig,
+        _delay: &mut D,
+    ) -> Result<(), Error>
+    where
+        DI: WriteOnlyDataCommand,
+        D: DelayNs,
+    {
+        init_column_mode_common(iface, Self::dimensions(), config).await
+    }
+}
+
+impl DisplayVariant for Ssd1306<Screen96x16> {
+    const WIDTH: u16 = 96;
+    const HEIGHT: u16 = 16;
+    type Buffer = [u8; 96 * 16 / 8];
+    const INIT_CONFIG: InitConfig = SEQUENTIAL_COM_INIT_CONFIG;
+}
+
+#[maybe_async_cfg::maybe(
+    sync(cfg(not(feature = "async")),),
+    async(feature = "async"),
+    keep_self
+)]
+impl MonochromeVariant for Ssd1306<Screen96x16> {
+    const HARDWARE_SCROLL: bool = true;
+
+    async fn init_column_mode<DI, D>(
+        iface: &mut DI,
+        config: &InitConfig,
+        _delay: &mut D,
+    ) -> Result<(), Error>
+    where
+        DI: WriteOnlyDataCommand,
+        D: DelayNs,
+    {
+        init_column_mode_common(iface, Self::dimensions(), config).await
+    }
+}
+
+impl DisplayVariant for Ssd1306<Screen72x40> {
+    const WIDTH: u16 = 72;
+    const HEIGHT: u16 = 40;
+    type Buffer = [u8; 72 * 40 / 8];
+}
+
+#[maybe_async_cfg::maybe(
+    sync(cfg(not(feature = "async")),),
+    async(feature = "async"),
+    keep_self
+)]
+impl MonochromeVariant for Ssd1306<Screen72x40> {
+    const HARDWARE_SCROLL: bool = true;
+    const COLUMN_OFFSET: u8 = 28;
+
+    async fn init_column_mode<DI, D>(
+        iface: &mut DI,
+        config: &InitConfig,
+        _delay: &mut D,
+    ) -> Result<(), Error>
+    where
+        DI: WriteOnlyDataCommand,
+        D: DelayNs,
+    {
+        init_column_mode_common(iface, Self::dimensions(), config).await
+    }
+}
+
+impl DisplayVariant for Ssd1306<Screen64x48> {
+    const WIDTH: u16 = 64;
+    const HEIGHT: u16 = 48;
+    type Buffer = [u8; 64 * 48 / 8];
+}
+
+#[maybe_async_cfg::maybe(
+    sync(cfg(not(feature = "async")),),
+    async(feature = "async"),
+    keep_self
+)]
+impl MonochromeVariant for Ssd1306<Screen64x48> {
+    const HARDWARE_SCROLL: bool = true;
+    const COLUMN_OFFSET: u8 = 32;
+
+    async fn init_column_mode<DI, D>(
+        iface: &mut DI,
+        config: &InitConfig,
+        _delay: &mut D,
+    ) -> Result<(), Error>
+    where
+        DI: WriteOnlyDataCommand,
+        D: DelayNs,
+    {
+        init_column_mode_common(iface, Self::dimensions(), config).await
+    }
+}
+
+/// Initialise the display in column mode (i.e. a byte walks down a column of 8
+/// pixels) with column 0 on the left and column _(display_width - 1)_ on the
+/// right.
+#[maybe_async_cfg::maybe(
+    sync(cfg(not(feature = "async")),),
+    async(feature = "async"),
+    keep_self
+)]
+async fn init_column_mode_common<DI>(
+    iface: &mut DI,
+    dimensions: (u16, u16),
+    config: &InitConfig,
+) -> Result<(), Error>
+where
+    DI: WriteOnlyDataCommand,
+{
+    let (_, display_height) = dimensions;
+
+    CommandBatch::new()
+        .push(Command::DisplayOn(false))
+        .push(Command::DisplayClockDiv(
+            config.oscillator_frequency,
+            config.clock_divide,
+        ))
+        .push(Command::Multiplex((display_height - 1) as u8))
+        .push(Command::DisplayOffset(config.display_offset))
+        .push(Command::StartLine(config.start_line))
+        .push(Command::ComPinConfig(config.com_pin_alternative))
+        // The SSD1306 uses 0x8D for the charge pump instead of the SH110x 0xAD.
+        // Display must be off when performing this command
+        .push(Command::SsdChargePump(config.charge_pump))
+        .push(Command::Contrast(config.contrast))
+        .push(Command::PreChargePeriod(
+            config.precharge.0,
+            config.precharge.1,
+        ))
+        .push(Command::VcomhDeselect(config.vcomh))
+        .push(Command::AllOn(false))
+        .push(Command::Invert(false))
+        .send(iface)
+        .await?;
+
+    Ok(())
+}
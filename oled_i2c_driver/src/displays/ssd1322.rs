@@ -0,0 +1,158 @@
+use core::marker::PhantomData;
+
+#[cfg(feature = "async")]
+use display_interface::AsyncWriteOnlyDataCommand as WriteOnlyDataCommand;
+use display_interface::DataFormat;
+#[cfg(not(feature = "async"))]
+use display_interface::WriteOnlyDataCommand;
+#[cfg(feature = "async")]
+use embedded_hal_async::delay::DelayNs;
+#[cfg(not(feature = "async"))]
+use hal::delay::DelayNs;
+
+use super::{DisplayVariant, GrayscaleVariant, InitConfig, Screen256x64};
+use crate::Error;
+
+/// Generic SSD1322 based 4-bit grayscale display
+#[derive(Debug, Clone, Copy)]
+pub struct Ssd1322<SS> {
+    _ss: PhantomData<SS>,
+}
+
+impl<SS> Ssd1322<SS> {
+    /// Create the variant, e.g. `Ssd1322::<Screen256x64>::new()`
+    pub const fn new() -> Self {
+        Ssd1322 { _ss: PhantomData }
+    }
+}
+
+impl<SS> Default for Ssd1322<SS> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// First column address of a 256 pixel wide panel in the 480 pixel RAM
+const FIRST_COLUMN: u8 = 0x1C;
+
+/// Pixels per column address
+const PIXELS_PER_COLUMN: u16 = 4;
+
+/// Power-up sequence from the datasheet's application example, as command
+/// and parameter bytes. The parameters taken from [`InitConfig`] are sent
+/// after it.
+const INIT_SEQUENCE: &[(u8, &[u8])] = &[
+    // Unlock the command interface
+    (0xFD, &[0x12]),
+    // Display off
+    (0xAE, &[]),
+    // Multiplex ratio, 64 rows
+    (0xCA, &[0x3F]),
+    // Horizontal address increment, nibble remap, dual COM line mode
+    (0xA0, &[0x14, 0x11]),
+    // Disable GPIO
+    (0xB5, &[0x00]),
+    // Internal VDD regulator
+    (0xAB, &[0x01]),
+    // Display enhancement A, external VSL
+    (0xB4, &[0xA0, 0xFD]),
+    // Master contrast current
+    (0xC7, &[0x0F]),
+    // Default linear grayscale table
+    (0xB9, &[]),
+    // Display enhancement B
+    (0xD1, &[0xA2, 0x20]),
+    // Precharge voltage
+    (0xBB, &[0x1F]),
+    // Second precharge period
+    (0xB6, &[0x08]),
+    // VCOMH
+    (0xBE, &[0x07]),
+    // Normal display
+    (0xA6, &[]),
+    // Exit partial display
+    (0xA9, &[]),
+];
+
+impl DisplayVariant for Ssd1322<Screen256x64> {
+    const WIDTH: u16 = 256;
+    const HEIGHT: u16 = 64;
+    type Buffer = [u8; 256 * 64 / 2];
+    const INIT_CONFIG: InitConfig = InitConfig {
+        contrast: 0x9F,
+        precharge: (0x2, 0xE),
+        oscillator_frequency: 0x9,
+        clock_divide: 0x1,
+        ..InitConfig::DEFAULT
+    };
+}
+
+#[maybe_async_cfg::maybe(
+    sync(cfg(not(feature = "async")),),
+    async(feature = "async"),
+    keep_self
+)]
+impl GrayscaleVariant for Ssd1322<Screen256x64> {
+    async fn init_grayscale<DI, D>(
+        iface: &mut DI,
+        config: &InitConfig,
+        _delay: &mut D,
+    ) -> Result<(), Error>
+    where
+        DI: WriteOnlyDataCommand,
+        D: DelayNs,
+    {
+        for (command, params) in INIT_SEQUENCE {
+            send(iface, *command, params).await?;
+        }
+
+        // The charge pump, VCOMH and COM pin settings have no equivalent here
+        let clock = ((0xF & config.oscillator_frequency) << 4)
+            | (0xF & config.clock_divide);
+        let precharge =
+            ((0xF & config.precharge.1) << 4) | (0xF & config.precharge.0);
+        send(iface, 0xB3, &[clock]).await?;
+        send(iface, 0xA2, &[config.display_offset]).await?;
+        send(iface, 0xA1, &[config.start_line]).await?;
+        send(iface, 0xC1, &[config.contrast]).await?;
+        send(iface, 0xB1, &[precharge]).await
+    }
+
+    async fn set_window<DI>(
+        iface: &mut DI,
+        columns: (u16, u16),
+        rows: (u16, u16),
+    ) -> Result<(), Error>
+    where
+        DI: WriteOnlyDataCommand,
+    {
+        let start = FIRST_COLUMN + (columns.0 / PIXELS_PER_COLUMN) as u8;
+        let end = FIRST_COLUMN + (columns.1 / PIXELS_PER_COLUMN) as u8 - 1;
+        send(iface, 0x15, &[start, end]).await?;
+        send(iface, 0x75, &[rows.0 as u8, (rows.1 - 1) as u8]).await?;
+        // Enable writing to RAM
+        send(iface, 0x5C, &[]).await
+    }
+}
+
+/// The SSD1322 takes command parameters as data bytes
+#[maybe_async_cfg::maybe(
+    sync(cfg(not(feature = "async")),),
+    async(feature = "async"),
+    keep_self
+)]
+async fn send<DI>(
+    iface: &mut DI,
+    command: u8,
+    params: &[u8],
+) -> Result<(), Error>
+where
+    DI: WriteOnlyDataCommand,
+{
+    iface.send_commands(DataFormat::U8(&[command])).await?;
+    if !params.is_empty() {
+        iface.send_data(DataFormat::U8(params)).await?;
+    }
+
+    Ok(())
+}
@@ -0,0 +1,144 @@
+use core::marker::PhantomData;
+
+#[cfg(feature = "async")]
+use display_interface::AsyncWriteOnlyDataCommand as WriteOnlyDataCommand;
+use display_interface::DataFormat;
+#[cfg(not(feature = "async"))]
+use display_interface::WriteOnlyDataCommand;
+#[cfg(feature = "async")]
+use embedded_hal_async::delay::DelayNs;
+#[cfg(not(feature = "async"))]
+use hal::delay::DelayNs;
+
+use super::{DisplayVariant, GrayscaleVariant, InitConfig, Screen128x128};
+use crate::{command::CommandBatch, Error};
+
+/// Generic SSD1327 based 4-bit grayscale display
+#[derive(Debug, Clone, Copy)]
+pub struct Ssd1327<SS> {
+    _ss: PhantomData<SS>,
+}
+
+impl<SS> Ssd1327<SS> {
+    /// Create the variant, e.g. `Ssd1327::<Screen128x128>::new()`
+    pub const fn new() -> Self {
+        Ssd1327 { _ss: PhantomData }
+    }
+}
+
+impl<SS> Default for Ssd1327<SS> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Pixels per column address
+const PIXELS_PER_COLUMN: u16 = 2;
+
+/// Power-up sequence, each entry being one command with its parameters. The
+/// parameters taken from [`InitConfig`] are sent after it.
+const INIT_SEQUENCE: &[&[u8]] = &[
+    // Display off
+    &[0xAE],
+    // Unlock the command interface
+    &[0xFD, 0x12],
+    // Multiplex ratio, 128 rows
+    &[0xA8, 0x7F],
+    // Column address remap, COM remap and split odd/even
+    &[0xA0, 0x51],
+    // Internal VDD regulator
+    &[0xAB, 0x01],
+    // Default linear grayscale table
+    &[0xB9],
+    // Precharge voltage
+    &[0xBC, 0x08],
+    // VCOMH
+    &[0xBE, 0x07],
+    // Second precharge period
+    &[0xB6, 0x01],
+    // Function selection B, second precharge enabled
+    &[0xD5, 0x62],
+    // Normal display
+    &[0xA4],
+    // Stop scrolling
+    &[0x2E],
+];
+
+impl DisplayVariant for Ssd1327<Screen128x128> {
+    const WIDTH: u16 = 128;
+    const HEIGHT: u16 = 128;
+    type Buffer = [u8; 128 * 128 / 2];
+    const INIT_CONFIG: InitConfig = InitConfig {
+        precharge: (0x1, 0x5),
+        oscillator_frequency: 0x0,
+        clock_divide: 0x1,
+        ..InitConfig::DEFAULT
+    };
+}
+
+#[maybe_async_cfg::maybe(
+    sync(cfg(not(feature = "async")),),
+    async(feature = "async"),
+    keep_self
+)]
+impl GrayscaleVariant for Ssd1327<Screen128x128> {
+    async fn init_grayscale<DI, D>(
+        iface: &mut DI,
+        config: &InitConfig,
+        _delay: &mut D,
+    ) -> Result<(), Error>
+    where
+        DI: WriteOnlyDataCommand,
+        D: DelayNs,
+    {
+        let batch = INIT_SEQUENCE
+            .iter()
+            .fold(CommandBatch::new(), |batch, command| {
+                batch.push_raw(command)
+            });
+
+        // The charge pump, VCOMH and COM pin settings have no equivalent here
+        let clock = ((0xF & config.oscillator_frequency) << 4)
+            | (0xF & config.clock_divide);
+        let precharge =
+            ((0xF & config.precharge.1) << 4) | (0xF & config.precharge.0);
+        batch
+            .push_raw(&[
+                0xA1,
+                config.start_line,
+                0xA2,
+                config.display_offset,
+                0x81,
+                config.contrast,
+                0xB1,
+                precharge,
+                0xB3,
+                clock,
+            ])
+            .send(iface)
+            .await
+    }
+
+    async fn set_window<DI>(
+        iface: &mut DI,
+        columns: (u16, u16),
+        rows: (u16, u16),
+    ) -> Result<(), Error>
+    where
+        DI: WriteOnlyDataCommand,
+    {
+        let start = (columns.0 / PIXELS_PER_COLUMN) as u8;
+        let end = (columns.1 / PIXELS_PER_COLUMN) as u8 - 1;
+        iface
+            .send_commands(DataFormat::U8(&[
+                0x15,
+                start,
+                end,
+                0x75,
+                rows.0 as u8,
+                (rows.1 - 1) as u8,
+            ]))
+            .await
+            .map_err(Error::Comm)
+    }
+}
@@ -0,0 +1,504 @@
+extern crate std;
+
+use std::vec::Vec;
+
+use super::{
+    sh1106::Sh1106,
+    sh1107::Sh1107,
+    sh1108::Sh1108,
+    sh1122::Sh1122,
+    ssd1306::Ssd1306,
+    ssd1309::Ssd1309,
+    ssd1327::Ssd1327,
+    DisplayVariant,
+    GrayscaleVariant,
+    InitConfig,
+    MonochromeVariant,
+    Screen128x128,
+    Screen128x32,
+    Screen128x64,
+    Screen160x160,
+    Screen256x64,
+    Screen64x128,
+    Screen72x40,
+};
+use crate::{
+    command::{Command, InitStep, NFrames, ScrollDirection},
+    displayrotation::DisplayRotation,
+    properties::DisplayProperties,
+    test_helpers::{
+        block_on,
+        decode,
+        PowerEvent,
+        PowerLog,
+        RecordingInterface,
+        Transfer,
+    },
+};
+
+/// Run the variant's initialisation with its default parameters
+fn init<DV: MonochromeVariant>() -> RecordingInterface {
+    let mut iface = RecordingInterface::new();
+    block_on(DV::init_column_mode(
+        &mut iface,
+        &DV::INIT_CONFIG,
+        &mut PowerLog::new(),
+    ))
+    .unwrap();
+    iface
+}
+
+/// Initialisation shared by the SH110x and SSD130x variants, with the charge
+/// pump command of the controller
+fn common_init(
+    height: u8,
+    com_pin_alternative: bool,
+    charge_pump: Command,
+) -> Vec<Command> {
+    std::vec![
+        Command::DisplayOn(false),
+        Command::DisplayClockDiv(0x8, 0x0),
+        Command::Multiplex(height - 1),
+        Command::DisplayOffset(0),
+        Command::StartLine(0),
+        Command::ComPinConfig(com_pin_alternative),
+        charge_pump,
+        Command::Contrast(0x80),
+        Command::PreChargePeriod(0x1, 0xF),
+        Command::VcomhDeselect(super::VcomhLevel::Auto),
+        Command::AllOn(false),
+        Command::Invert(false),
+    ]
+}
+
+#[test]
+fn ssd1306_init() {
+    let iface = init::<Ssd1306<Screen128x64>>();
+    assert_eq!(
+        iface.commands::<Ssd1306<Screen128x64>>(),
+        common_init(64, true, Command::SsdChargePump(true))
+    );
+    assert_eq!(iface.command_transfers(), 1);
+
+    let iface = init::<Ssd1306<Screen128x32>>();
+    assert_eq!(
+        iface.commands::<Ssd1306<Screen128x32>>(),
+        common_init(32, false, Command::SsdChargePump(true))
+    );
+
+    let iface = init::<Ssd1306<Screen72x40>>();
+    assert_eq!(
+        iface.commands::<Ssd1306<Screen72x40>>(),
+        common_init(40, true, Command::SsdChargePump(true))
+    );
+}
+
+#[test]
+fn ssd1309_init() {
+    let iface = init::<Ssd1309<Screen128x64>>();
+    assert_eq!(
+        iface.commands::<Ssd1309<Screen128x64>>(),
+        common_init(64, true, Command::ChargePump(true))
+    );
+    assert_eq!(iface.command_transfers(), 1);
+}
+
+#[test]
+fn sh1106_init() {
+    let iface = init::<Sh1106<Screen128x64>>();
+    assert_eq!(
+        iface.commands::<Sh1106<Screen128x64>>(),
+        common_init(64, true, Command::ChargePump(true))
+    );
+    assert_eq!(iface.command_transfers(), 1);
+}
+
+#[test]
+fn sh1107_init() {
+    let iface = init::<Sh1107<Screen128x128>>();
+    let mut expected = common_init(128, true, Command::ChargePump(true));
+    expected[4] = Command::LargeStartLine(0);
+    assert_eq!(iface.commands::<Sh1107<Screen128x128>>(), expected);
+    assert_eq!(iface.command_transfers(), 1);
+}
+
+#[test]
+fn sh1108_init() {
+    let iface = init::<Sh1108<Screen160x160>>();
+    assert_eq!(
+        iface.commands::<Sh1108<Screen160x160>>(),
+        [
+            Command::DisplayOn(false),
+            Command::DisplayClockDiv(0x6, 0x0),
+            Command::DisplayResolution(3),
+            Command::PreChargePeriod(0x8, 0x2),
+            Command::Multiplex(159),
+            Command::DisplayOffset(0),
+            Command::LargeStartLine(0),
+            Command::ComPinConfig(true),
+            Command::ChargePump(true),
+            Command::Contrast(0x80),
+            Command::PreChargePeriod(0x1, 0xF),
+            Command::VcomhDeselect(super::VcomhLevel::Auto),
+            Command::AllOn(false),
+            Command::Invert(false),
+        ]
+    );
+    assert_eq!(iface.command_transfers(), 1);
+}
+
+#[test]
+fn sh1122_init() {
+    let mut iface = RecordingInterface::new();
+    block_on(Sh1122::<Screen256x64>::init_grayscale(
+        &mut iface,
+        &Sh1122::<Screen256x64>::INIT_CONFIG,
+        &mut PowerLog::new(),
+    ))
+    .unwrap();
+    // The SH1122 shares these opcodes with the page addressed controllers
+    assert_eq!(
+        decode(&iface.command_bytes(), false),
+        [
+            Command::DisplayOn(false),
+            Command::DisplayClockDiv(0x5, 0x0),
+            Command::Multiplex(63),
+            Command::DisplayOffset(0),
+            Command::StartLine(0),
+            Command::SegmentRemap(false),
+            Command::ReverseComDir(false),
+            Command::Contrast(0x80),
+            Command::PreChargePeriod(0x2, 0x2),
+            Command::AllOn(false),
+            Command::Invert(false),
+        ]
+    );
+}
+
+#[test]
+fn init_config_is_applied() {
+    let config = InitConfig {
+        contrast: 0x20,
+        charge_pump: false,
+        start_line: 8,
+        ..InitConfig::DEFAULT
+    };
+    let mut iface = RecordingInterface::new();
+    block_on(Ssd1306::<Screen128x64>::init_column_mode(
+        &mut iface,
+        &config,
+        &mut PowerLog::new(),
+    ))
+    .unwrap();
+
+    let commands = iface.commands::<Ssd1306<Screen128x64>>();
+    assert!(commands.contains(&Command::Contrast(0x20)));
+    assert!(commands.contains(&Command::SsdChargePump(false)));
+    assert!(commands.contains(&Command::StartLine(8)));
+}
+
+/// Variant defined through its initialisation sequence only
+struct Sequenced;
+
+impl DisplayVariant for Sequenced {
+    const WIDTH: u16 = 128;
+    const HEIGHT: u16 = 64;
+    type Buffer = [u8; 128 * 64 / 8];
+}
+
+impl MonochromeVariant for Sequenced {
+    const INIT_SEQUENCE: &'static [InitStep<'static>] = &[
+        InitStep::Command(Command::DisplayOn(false)),
+        InitStep::Raw(&[0x8D, 0x14]),
+        InitStep::DelayMs(100),
+        InitStep::Command(Command::Contrast(0x80)),
+    ];
+}
+
+#[test]
+fn init_sequence_is_batched_between_delays() {
+    let iface = init::<Sequenced>();
+    assert_eq!(
+        iface.transfers,
+        [
+            Transfer::Commands(std::vec![0xAE, 0x8D, 0x14]),
+            Transfer::Commands(std::vec![0x81, 0x80]),
+        ]
+    );
+}
+
+/// Draw one byte at `column` of `page` and return what was sent
+fn draw_at<DV: MonochromeVariant>(
+    variant: DV,
+    column: u8,
+    page: u8,
+) -> RecordingInterface {
+    let mut properties = DisplayProperties::new(
+        variant,
+        RecordingInterface::new(),
+        DisplayRotation::Rotate0,
+    );
+    let column = column + DV::COLUMN_OFFSET;
+    block_on(properties.set_draw_area((column, page), (column + 1, page + 1)))
+        .unwrap();
+    block_on(properties.draw(&[0xAA])).unwrap();
+
+    properties.release()
+}
+
+#[test]
+fn page_addressing() {
+    let iface = draw_at(Ssd1306::<Screen128x64>::new(), 0x25, 3);
+    assert_eq!(
+        iface.transfers[0],
+        Transfer::Commands(std::vec![0xB3, 0x05, 0x12])
+    );
+    assert_eq!(iface.transfers[1], Transfer::Data(std::vec![0xAA]));
+}
+
+#[test]
+fn exact_fill_sends_no_trailing_address() {
+    let mut properties = DisplayProperties::new(
+        Ssd1306::<Screen128x64>::new(),
+        RecordingInterface::new(),
+        DisplayRotation::Rotate0,
+    );
+    block_on(properties.set_draw_area((4, 1), (6, 3))).unwrap();
+    block_on(properties.draw(&[1, 2, 3, 4])).unwrap();
+
+    // Each row is addressed once, and nothing follows the last byte
+    assert_eq!(
+        properties.release().transfers,
+        [
+            Transfer::Commands(std::vec![0xB1, 0x04, 0x10]),
+            Transfer::Data(std::vec![1, 2]),
+            Transfer::Commands(std::vec![0xB2, 0x04, 0x10]),
+            Transfer::Data(std::vec![3, 4]),
+        ]
+    );
+}
+
+#[test]
+fn column_offset_is_applied() {
+    let iface = draw_at(Sh1106::<Screen128x64>::new(), 0, 0);
+    assert_eq!(
+        iface.commands::<Sh1106<Screen128x64>>()[..3],
+        [
+            Command::PageAddress(0),
+            Command::ColumnAddressLow(2),
+            Command::ColumnAddressHigh(0),
+        ]
+    );
+}
+
+#[test]
+fn large_page_addressing() {
+    let iface = draw_at(Sh1108::<Screen160x160>::new(), 0x9F, 19);
+    assert_eq!(
+        iface.commands::<Sh1108<Screen160x160>>()[..3],
+        [
+            Command::LargePageAddress(19),
+            Command::ColumnAddressLow(0xF),
+            Command::ColumnAddressHigh(0x9),
+        ]
+    );
+}
+
+#[test]
+fn draw_area_must_fit_the_panel() {
+    let mut properties = DisplayProperties::new(
+        Ssd1306::<Screen72x40>::new(),
+        RecordingInterface::new(),
+        DisplayRotation::Rotate0,
+    );
+
+    // Columns 28..100 and pages 0..5 are on the panel
+    assert!(block_on(properties.set_draw_area((28, 0), (100, 5))).is_ok());
+    assert!(block_on(properties.set_draw_area((27, 0), (100, 5))).is_err());
+    assert!(block_on(properties.set_draw_area((28, 0), (101, 5))).is_err());
+    assert!(block_on(properties.set_draw_area((28, 0), (100, 6))).is_err());
+}
+
+#[test]
+fn start_line_range_follows_the_controller() {
+    let mut properties = DisplayProperties::new(
+        Sh1107::<Screen64x128>::new(),
+        RecordingInterface::new(),
+        DisplayRotation::Rotate0,
+    );
+    block_on(properties.set_start_line(100)).unwrap();
+    assert!(block_on(properties.set_start_line(128)).is_err());
+    block_on(properties.set_scroll_position(130)).unwrap();
+    assert_eq!(
+        properties.release().transfers,
+        [
+            Transfer::Commands(std::vec![0xDC, 100]),
+            Transfer::Commands(std::vec![0xDC, 2]),
+        ]
+    );
+
+    // The single byte command reaches all 64 RAM rows of a 32 row panel
+    let mut properties = DisplayProperties::new(
+        Ssd1306::<Screen128x32>::new(),
+        RecordingInterface::new(),
+        DisplayRotation::Rotate0,
+    );
+    block_on(properties.set_start_line(40)).unwrap();
+    assert!(block_on(properties.set_start_line(64)).is_err());
+    assert_eq!(
+        properties.release().transfers,
+        [Transfer::Commands(std::vec![0x68])]
+    );
+}
+
+#[test]
+fn display_offset_range_follows_the_controller() {
+    let mut properties = DisplayProperties::new(
+        Sh1107::<Screen64x128>::new(),
+        RecordingInterface::new(),
+        DisplayRotation::Rotate0,
+    );
+    block_on(properties.set_display_offset(127)).unwrap();
+    assert!(block_on(properties.set_display_offset(128)).is_err());
+    assert_eq!(
+        properties.release().transfers,
+        [Transfer::Commands(std::vec![0xD3, 127])]
+    );
+
+    // A 32 row panel can still be shifted across all 64 outputs
+    let mut properties = DisplayProperties::new(
+        Ssd1306::<Screen128x32>::new(),
+        RecordingInterface::new(),
+        DisplayRotation::Rotate0,
+    );
+    block_on(properties.set_display_offset(63)).unwrap();
+    assert!(block_on(properties.set_display_offset(64)).is_err());
+    assert_eq!(
+        properties.release().transfers,
+        [Transfer::Commands(std::vec![0xD3, 63])]
+    );
+}
+
+#[test]
+fn horizontal_scroll_columns_follow_the_controller() {
+    let mut properties = DisplayProperties::new(
+        Ssd1309::<Screen128x64>::new(),
+        RecordingInterface::new(),
+        DisplayRotation::Rotate0,
+    );
+    block_on(properties.setup_scroll(
+        ScrollDirection::Left,
+        1,
+        6,
+        NFrames::F2,
+        0,
+    ))
+    .unwrap();
+    assert_eq!(
+        properties.release().transfers,
+        [Transfer::Commands(std::vec![
+            0x2E, 0x27, 0x00, 1, 0b111, 6, 0x00, 0x7F
+        ])]
+    );
+
+    let mut properties = DisplayProperties::new(
+        Ssd1306::<Screen128x32>::new(),
+        RecordingInterface::new(),
+        DisplayRotation::Rotate0,
+    );
+    block_on(properties.setup_scroll(
+        ScrollDirection::Right,
+        0,
+        3,
+        NFrames::F2,
+        0,
+    ))
+    .unwrap();
+    assert_eq!(
+        properties.release().transfers,
+        [Transfer::Commands(std::vec![
+            0x2E, 0x26, 0x00, 0, 0b111, 3, 0x00, 0xFF
+        ])]
+    );
+}
+
+#[test]
+fn grayscale_window() {
+    let mut iface = RecordingInterface::new();
+    block_on(Ssd1327::<Screen128x128>::set_window(
+        &mut iface,
+        (16, 128),
+        (4, 8),
+    ))
+    .unwrap();
+    assert_eq!(
+        iface.transfers,
+        [Transfer::Commands(std::vec![0x15, 8, 63, 0x75, 4, 7])]
+    );
+
+    let mut iface = RecordingInterface::new();
+    block_on(Sh1122::<Screen256x64>::set_window(
+        &mut iface,
+        (32, 256),
+        (9, 10),
+    ))
+    .unwrap();
+    // The row address takes a parameter byte like the SH1108 page address
+    assert_eq!(
+        decode(&iface.command_bytes(), true),
+        [
+            Command::LargePageAddress(9),
+            Command::ColumnAddressLow(0),
+            Command::ColumnAddressHigh(1),
+        ]
+    );
+}
+
+#[test]
+fn display_is_switched_on_once_vcc_has_settled() {
+    let log = PowerLog::new();
+    let mut properties = DisplayProperties::new(
+        Ssd1306::<Screen128x64>::new(),
+        log.clone(),
+        DisplayRotation::Rotate0,
+    )
+    .with_power(Some(log.pin("reset")), Some(log.pin("vcc")), log.clone());
+    block_on(properties.init_column_mode()).unwrap();
+
+    let events = log.events();
+    assert_eq!(
+        events[..6],
+        [
+            PowerEvent::Pin("reset", true),
+            PowerEvent::DelayMs(1),
+            PowerEvent::Pin("reset", false),
+            PowerEvent::DelayMs(10),
+            PowerEvent::Pin("reset", true),
+            PowerEvent::DelayMs(1),
+        ]
+    );
+    // The init commands and rotation, then VCC, then display on
+    assert_eq!(
+        events[events.len() - 3..],
+        [
+            PowerEvent::Pin("vcc", true),
+            PowerEvent::DelayMs(100),
+            PowerEvent::Transfer(Transfer::Commands(std::vec![0xAF])),
+        ]
+    );
+
+    let commands: Vec<u8> = events
+        .iter()
+        .filter_map(|event| match event {
+            PowerEvent::Transfer(Transfer::Commands(bytes)) => Some(bytes),
+            _ => None,
+        })
+        .flatten()
+        .copied()
+        .collect();
+    let display_on = decode(&commands, false)
+        .into_iter()
+        .filter(|&command| command == Command::DisplayOn(true))
+        .count();
+    assert_eq!(display_on, 1);
+}
@@ -0,0 +1,27 @@
+//! Errors in this crate
+
+use display_interface::DisplayError;
+use hal::digital::ErrorKind;
+
+/// Error returned by the display properties and modes
+///
+/// Pin errors are reported by their [`ErrorKind`], so the reset and VCC pins
+/// don't need to share an error type.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum Error<CommE = DisplayError, PinE = ErrorKind> {
+    /// Communication error
+    Comm(CommE),
+    /// Pin setting error
+    Pin(PinE),
+    /// Draw area or position outside of the display
+    OutOfBounds,
+    /// Feature not supported by the display controller
+    Unsupported,
+}
+
+impl<PinE> From<DisplayError> for Error<DisplayError, PinE> {
+    fn from(error: DisplayError) -> Self {
+        Error::Comm(error)
+    }
+}
@@ -1,6 +1,8 @@
 //! The driver must be initialised by passing an instance that implements the
-//! AsyncWriteOnlyDataCommand trait from the display-interface crate. Usually
-//! this is either:
+//! AsyncWriteOnlyDataCommand trait from the display-interface crate, or the
+//! blocking WriteOnlyDataCommand trait when the default `async` feature is
+//! disabled. All methods that talk to the display are then plain blocking
+//! functions instead of `async fn`s. Usually the interface is either:
 //! * display_interface_spi::SPIInterface<...> or
 //! * display_interface_i2c::I2CInterface<...>
 //!
@@ -12,31 +14,38 @@
 //! example, to initialise the display with an I2C interface and
 //! [`mode::GraphicsMode`](mode/graphics/struct.GraphicsMode.html), you would do
 //! something like this:
+#![cfg_attr(feature = "async", doc = "```rust,no_run")]
+#![cfg_attr(not(feature = "async"), doc = "```rust,ignore")]
+//! # use display_interface::AsyncWriteOnlyDataCommand;
+//! # use embedded_hal::digital::OutputPin;
+//! # use embedded_hal_async::delay::DelayNs;
+//! use oled_i2c_driver::{
+//!     displays::{sh1107::Sh1107, Screen128x128},
+//!     prelude::*,
+//!     Builder,
+//! };
 //!
-//! ```rust,no_run
-//! use oled_async::{prelude::*, Builder};
-//! use oled_async::displays::sh1107::Sh1107_128_128;
-//!
-//! let raw_disp = Builder::new(Sh1107_128_128 {})
-//!     .with_rotation(crate::DisplayRotation::Rotate180)
+//! # async fn run(
+//! #     display_interface: impl AsyncWriteOnlyDataCommand,
+//! #     reset: impl OutputPin,
+//! #     delay: impl DelayNs,
+//! # ) {
+//! let raw_disp = Builder::new(Sh1107::<Screen128x128>::new())
+//!     .with_rotation(DisplayRotation::Rotate180)
+//!     .with_reset_pin(reset)
+//!     .with_delay(delay)
 //!     .connect(display_interface);
-//! let mut display: GraphicsMode<_, _> = raw_disp.into();
-//! display.reset(&mut reset, &mut delay).unwrap();
+//! let mut display: GraphicsMode<_, _, _, _, _> = raw_disp.into();
 //! display.init().await.unwrap();
 //! display.clear();
 //! display.flush().await.unwrap();
 //!
-//!
-//! let mut display: GraphicsMode<_> = Builder::new().connect_i2c(i2c).into();
-//!
-//! display.init().unwrap();
-//! display.flush().unwrap();
-//!
 //! display.set_pixel(10, 20, 1);
 //!
 //! display.flush().await.unwrap();
+//! # }
 //! ```
-//!
+//! 
 //! See the [examples](https://github.com/cschuhen/oled_drivers/tree/master/examples)
 //! for more usage. The [entire `embedded_graphics` featureset](https://github.com/jamwaffles/embedded-graphics#features)
 //! is supported by this driver.
@@ -51,39 +60,52 @@
 //!
 //! Uses [mode::GraphicsMode] and
 //! [embedded_graphics](../embedded_graphics/index.html).
-//!
-//! ```rust,no_run
+#![cfg_attr(
+    all(feature = "async", feature = "graphics"),
+    doc = "```rust,no_run"
+)]
+#![cfg_attr(
+    not(all(feature = "async", feature = "graphics")),
+    doc = "```rust,ignore"
+)]
 //! use embedded_graphics::{
 //!     mono_font::{ascii::FONT_6X10, MonoTextStyleBuilder},
 //!     pixelcolor::BinaryColor,
 //!     prelude::*,
 //!     text::{Baseline, Text},
 //! };
-//! use oled_async::{prelude::*, Builder};
-//! # let display_interface # See display_interface crate or examples
+//! # use display_interface::AsyncWriteOnlyDataCommand;
+//! use oled_i2c_driver::{
+//!     displays::{sh1107::Sh1107, Screen128x128},
+//!     prelude::*,
+//!     Builder,
+//! };
 //!
-//! let mut display: GraphicsMode<_, _> = Builder::new(oled_async::displays::sh1107::Sh1107_128_128 {})
-//!         .with_rotation(crate::DisplayRotation::Rotate180)
+//! # async fn run(display_interface: impl AsyncWriteOnlyDataCommand) {
+//! let mut display: GraphicsMode<_, _> =
+//!     Builder::new(Sh1107::<Screen128x128>::new())
+//!         .with_rotation(DisplayRotation::Rotate180)
 //!         .connect(display_interface)
 //!         .into();
 //!
-//! display.init().unwrap();
-//! display.flush().unwrap();
+//! display.init().await.unwrap();
+//! display.flush().await.unwrap();
 //!
 //! let text_style = MonoTextStyleBuilder::new()
 //!     .font(&FONT_6X10)
 //!     .text_color(BinaryColor::On)
 //!     .build();
 //!
-//! Text::with_baseline("Hello world!", Point::zero(), text_style, Baseline::Top)
-//!     .draw(&mut display)
+//! Text::with_baseline("Hello world!", Point::zero(), text_style,
+//! Baseline::Top)     .draw(&mut display)
 //!     .unwrap();
 //!
-//! Text::with_baseline("Hello Rust!", Point::new(0, 16), text_style, Baseline::Top)
-//!     .draw(&mut display)
+//! Text::with_baseline("Hello Rust!", Point::new(0, 16), text_style,
+//! Baseline::Top)     .draw(&mut display)
 //!     .unwrap();
 //!
-//! display.flush().unwrap();
+//! display.flush().await.unwrap();
+//! # }
 //! ```
 
 #![no_std]
@@ -96,24 +118,22 @@
 #![deny(unused_import_braces)]
 #![deny(unused_qualifications)]
 
-/// Errors in this crate
-#[derive(Debug)]
-pub enum Error<CommE, PinE> {
-    /// Communication error
-    Comm(CommE),
-    /// Pin setting error
-    Pin(PinE),
-}
-
 extern crate embedded_hal as hal;
 
 pub mod builder;
-mod command;
+pub mod command;
 pub mod displayrotation;
 pub mod displays;
+pub mod error;
 pub mod mode;
 pub mod prelude;
 pub mod properties;
-#[doc(hidden)]
-//pub mod test_helpers;
-pub use crate::builder::{Builder, NoOutputPin};
+#[cfg(feature = "std")]
+pub mod snapshot;
+#[cfg(test)]
+mod test_helpers;
+
+pub use crate::{
+    builder::{Builder, NoDelay, NoOutputPin, PowerDelay},
+    error::Error,
+};
@@ -1,4 +1,7 @@
-use display_interface::AsyncWriteOnlyDataCommand;
+#[cfg(feature = "async")]
+use display_interface::AsyncWriteOnlyDataCommand as WriteOnlyDataCommand;
+#[cfg(not(feature = "async"))]
+use display_interface::WriteOnlyDataCommand;
 
 use crate::properties::DisplayProperties;
 
@@ -6,20 +9,22 @@ use crate::properties::DisplayProperties;
 pub struct DisplayMode<MODE>(pub MODE);
 
 /// Trait with core functionality for display mode switching
-pub trait DisplayModeTrait<DV, DI> {
+pub trait DisplayModeTrait<DV, DI, RST, VCC, D> {
     /// Allocate all required data and initialise display for mode
-    fn new(properties: DisplayProperties<DV, DI>) -> Self;
+    fn new(properties: DisplayProperties<DV, DI, RST, VCC, D>) -> Self;
 
     /// Release resources for reuse with different mode
-    fn release(self) -> DisplayProperties<DV, DI>;
+    fn release(self) -> DisplayProperties<DV, DI, RST, VCC, D>;
 }
 
 impl<MODE> DisplayMode<MODE> {
     /// Setup display to run in requested mode
-    pub fn new<DV, DI>(properties: DisplayProperties<DV, DI>) -> Self
+    pub fn new<DV, DI, RST, VCC, D>(
+        properties: DisplayProperties<DV, DI, RST, VCC, D>,
+    ) -> Self
     where
-        DI: AsyncWriteOnlyDataCommand,
-        MODE: DisplayModeTrait<DV, DI>,
+        DI: WriteOnlyDataCommand,
+        MODE: DisplayModeTrait<DV, DI, RST, VCC, D>,
     {
         DisplayMode(MODE::new(properties))
     }
@@ -27,10 +32,11 @@ impl<MODE> DisplayMode<MODE> {
     /// Change into any mode implementing DisplayModeTrait
     // TODO: Figure out how to stay as generic DisplayMode but act as particular
     // mode
-    pub fn into<DV, DI, NMODE: DisplayModeTrait<DV, DI>>(self) -> NMODE
+    pub fn into<DV, DI, RST, VCC, D, NMODE>(self) -> NMODE
     where
-        DI: AsyncWriteOnlyDataCommand,
-        MODE: DisplayModeTrait<DV, DI>,
+        DI: WriteOnlyDataCommand,
+        MODE: DisplayModeTrait<DV, DI, RST, VCC, D>,
+        NMODE: DisplayModeTrait<DV, DI, RST, VCC, D>,
     {
         let properties = self.0.release();
         NMODE::new(properties)
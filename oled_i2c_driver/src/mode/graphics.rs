@@ -1,20 +1,40 @@
 //! Buffered display module for use with the [embedded-graphics] crate
-//!
-//! ```rust,no_run
-//! 
+#![cfg_attr(
+    all(feature = "async", feature = "graphics"),
+    doc = "```rust,no_run"
+)]
+#![cfg_attr(
+    not(all(feature = "async", feature = "graphics")),
+    doc = "```rust,ignore"
+)]
 //! use embedded_graphics::{
 //!     mono_font::{ascii::FONT_6X10, MonoTextStyleBuilder},
 //!     pixelcolor::BinaryColor,
 //!     prelude::*,
 //!     text::{Baseline, Text},
 //! };
-//! async fn run_display(display_interface: SomeInstanceOfDisplayInterface) {
-//!     let mut disp: GraphicsMode<_, _> = Builder::new(Display {})
-//!         .with_rotation(crate::DisplayRotation::Rotate180)
-//!         .connect(display_interface)
-//!         .into();
+//! # use display_interface::AsyncWriteOnlyDataCommand;
+//! # use embedded_hal::digital::OutputPin;
+//! # use embedded_hal_async::delay::DelayNs;
+//! use oled_i2c_driver::{
+//!     displays::{sh1106::Sh1106, Screen128x64},
+//!     prelude::*,
+//!     Builder,
+//! };
+//!
+//! async fn run_display(
+//!     display_interface: impl AsyncWriteOnlyDataCommand,
+//!     reset: impl OutputPin,
+//!     delay: impl DelayNs,
+//! ) {
+//!     let mut disp: GraphicsMode<_, _, _, _, _> =
+//!         Builder::new(Sh1106::<Screen128x64>::new())
+//!             .with_rotation(DisplayRotation::Rotate180)
+//!             .with_reset_pin(reset)
+//!             .with_delay(delay)
+//!             .connect(display_interface)
+//!             .into();
 //!
-//!     disp.reset(&mut reset, &mut delay).unwrap();
 //!     disp.init().await.unwrap();
 //!     disp.clear();
 //!     disp.flush().await.unwrap();
@@ -23,86 +43,236 @@
 //!         .font(&FONT_6X10)
 //!         .text_color(BinaryColor::On)
 //!         .build();
-//!     Text::with_baseline("Hello world!", Point::zero(), text_style, Baseline::Top)
-//!         .draw(&mut disp)
+//!     Text::with_baseline("Hello world!", Point::zero(), text_style,
+//! Baseline::Top)         .draw(&mut disp)
 //!         .unwrap();
 //!
 //!     disp.flush().await.unwrap();
 //! }
 //! ```
 
-use display_interface::{AsyncWriteOnlyDataCommand, DisplayError};
-use hal::{delay::DelayNs, digital::OutputPin};
+#[cfg(feature = "graphics")]
+use core::ops::RangeInclusive;
+
+#[cfg(feature = "async")]
+use display_interface::AsyncWriteOnlyDataCommand as WriteOnlyDataCommand;
+#[cfg(not(feature = "async"))]
+use display_interface::WriteOnlyDataCommand;
+#[cfg(feature = "graphics")]
+use embedded_graphics_core::{
+    draw_target::DrawTarget,
+    geometry::{Dimensions, OriginDimensions, Size},
+    pixelcolor::BinaryColor,
+    primitives::Rectangle,
+    Pixel,
+};
+#[cfg(feature = "async")]
+use embedded_hal_async::delay::DelayNs;
+#[cfg(not(feature = "async"))]
+use hal::delay::DelayNs;
+use hal::digital::OutputPin;
 
 use crate::{
+    builder::{NoDelay, NoOutputPin, PowerDelay},
+    command::{NFrames, ScrollDirection},
     displayrotation::DisplayRotation,
-    displays::DisplayVariant,
+    displays::{Framebuffer, MonochromeVariant},
     mode::displaymode::DisplayModeTrait,
     properties::DisplayProperties,
+    Error,
 };
 
-const DEFAULT_BUFFER_SIZE: usize = 160 * 160 / 8;
+/// Most pages a panel can have, i.e. 256 rows
+const MAX_PAGES: usize = 32;
+
+/// Widest panel whose pages `fill_contiguous` gathers in one go
+#[cfg(feature = "graphics")]
+const MAX_COLUMNS: usize = 256;
+
+/// Column range `start..end` of a page that differs from the display
+#[derive(Clone, Copy)]
+struct DirtyColumns {
+    start: u16,
+    end: u16,
+}
+
+impl DirtyColumns {
+    const CLEAN: DirtyColumns = DirtyColumns { start: 0, end: 0 };
+
+    fn is_clean(&self) -> bool {
+        self.start >= self.end
+    }
+
+    fn add(&mut self, column: u16) {
+        if self.is_clean() {
+            *self = DirtyColumns {
+                start: column,
+                end: column + 1,
+            };
+        } else {
+            self.start = self.start.min(column);
+            self.end = self.end.max(column + 1);
+        }
+    }
+}
 
 /// Graphics mode handler
-pub struct GraphicsMode<DV, DI, const BS: usize = DEFAULT_BUFFER_SIZE>
-where
-    DI: AsyncWriteOnlyDataCommand,
-    DV: DisplayVariant,
+///
+/// Drawing keeps track of the changed columns of every page, so
+/// [`flush`](GraphicsMode::flush) only sends what is out of date on the
+/// display.
+pub struct GraphicsMode<
+    DV,
+    DI,
+    RST = NoOutputPin,
+    VCC = NoOutputPin,
+    D = NoDelay,
+> where
+    DI: WriteOnlyDataCommand,
+    DV: MonochromeVariant,
 {
-    properties: DisplayProperties<DV, DI>,
-    buffer: [u8; BS],
+    properties: DisplayProperties<DV, DI, RST, VCC, D>,
+    buffer: DV::Buffer,
+    dirty: [DirtyColumns; MAX_PAGES],
 }
 
-impl<DV, DI, const BS: usize> DisplayModeTrait<DV, DI>
-    for GraphicsMode<DV, DI, BS>
+impl<DV, DI, RST, VCC, D> DisplayModeTrait<DV, DI, RST, VCC, D>
+    for GraphicsMode<DV, DI, RST, VCC, D>
 where
-    DI: AsyncWriteOnlyDataCommand,
-    DV: DisplayVariant,
+    DI: WriteOnlyDataCommand,
+    DV: MonochromeVariant,
 {
     /// Create new GraphicsMode instance
-    fn new(properties: DisplayProperties<DV, DI>) -> Self {
-        GraphicsMode {
+    fn new(properties: DisplayProperties<DV, DI, RST, VCC, D>) -> Self {
+        const {
+            assert!(
+                DV::Buffer::LEN * 8 >= DV::WIDTH as usize * DV::HEIGHT as usize,
+                "the variant's buffer is too small for one bit per pixel"
+            )
+        };
+        #[cfg(feature = "graphics")]
+        const {
+            assert!(
+                DV::WIDTH as usize <= MAX_COLUMNS,
+                "the panel is too wide for GraphicsMode"
+            )
+        };
+
+        let mut mode = GraphicsMode {
             properties,
-            buffer: [0u8; BS],
-        }
+            buffer: DV::Buffer::ZEROED,
+            dirty: [DirtyColumns::CLEAN; MAX_PAGES],
+        };
+        // Nothing is known about the display RAM contents yet
+        mode.mark_all_dirty();
+        mode
     }
 
     /// Release all resources used by GraphicsMode
-    fn release(self) -> DisplayProperties<DV, DI> {
+    fn release(self) -> DisplayProperties<DV, DI, RST, VCC, D> {
         self.properties
     }
 }
 
-impl<DV, DI, const BS: usize> GraphicsMode<DV, DI, BS>
+#[maybe_async_cfg::maybe(
+    sync(cfg(not(feature = "async")),),
+    async(feature = "async"),
+    keep_self
+)]
+impl<DV, DI, RST, VCC, D> GraphicsMode<DV, DI, RST, VCC, D>
 where
-    DI: AsyncWriteOnlyDataCommand,
-    DV: DisplayVariant,
+    DI: WriteOnlyDataCommand,
+    DV: MonochromeVariant,
 {
     /// Clear the display buffer. You need to call `display.flush()` for any
     /// effect on the screen
     pub fn clear(&mut self) {
-        self.buffer = [0; BS];
+        self.buffer = DV::Buffer::ZEROED;
+        self.mark_all_dirty();
+    }
+
+    fn mark_all_dirty(&mut self) {
+        let (display_width, display_height) = DV::dimensions();
+        let pages = usize::from(display_height / 8).min(MAX_PAGES);
+        for page in self.dirty[..pages].iter_mut() {
+            *page = DirtyColumns {
+                start: 0,
+                end: display_width,
+            };
+        }
+    }
+
+    /// Write out the parts of the buffer that changed since the last flush
+    pub async fn flush(&mut self) -> Result<(), Error> {
+        let (_, display_height) = DV::dimensions();
+        let pages = usize::from(display_height / 8).min(MAX_PAGES);
+
+        for page in 0..pages {
+            if self.dirty[page].is_clean() {
+                continue;
+            }
+            let DirtyColumns { start, end } = self.dirty[page];
+            self.flush_page(page, start, end).await?;
+        }
+
+        Ok(())
+    }
+
+    /// Write out only the part of the buffer covering `area`, given in
+    /// display coordinates (i.e. after rotation). The area is rounded out to
+    /// whole pages.
+    #[cfg(feature = "graphics")]
+    pub async fn flush_area(&mut self, area: Rectangle) -> Result<(), Error> {
+        let Some((columns, rows)) = self.panel_area(&area) else {
+            return Ok(());
+        };
+
+        let (start, end) = (*columns.start() as u16, *columns.end() as u16 + 1);
+        let (first_page, last_page) =
+            (*rows.start() as usize / 8, *rows.end() as usize / 8);
+
+        for page in first_page..=last_page.min(MAX_PAGES - 1) {
+            self.flush_page(page, start, end).await?;
+        }
+
+        Ok(())
     }
 
-    /// Reset display
-    pub fn reset<RST, DELAY, PinE>(
+    /// Send columns `start..end` of `page` and update the dirty state
+    async fn flush_page(
         &mut self,
-        rst: &mut RST,
-        delay: &mut DELAY,
-    ) -> Result<(), PinE>
-    where
-        RST: OutputPin<Error = PinE>,
-        DELAY: DelayNs,
-    {
-        rst.set_high()?;
-        delay.delay_ms(1);
-        rst.set_low()?;
-        delay.delay_ms(10);
-        rst.set_high()
+        page: usize,
+        start: u16,
+        end: u16,
+    ) -> Result<(), Error> {
+        let (display_width, _) = DV::dimensions();
+        let column_offset = DV::COLUMN_OFFSET;
+
+        self.properties
+            .set_draw_area(
+                (start as u8 + column_offset, page as u8),
+                (end as u8 + column_offset, page as u8 + 1),
+            )
+            .await?;
+
+        let page_start = page * usize::from(display_width);
+        self.properties
+            .draw(
+                &self.buffer.as_ref()[page_start + usize::from(start)
+                    ..page_start + usize::from(end)],
+            )
+            .await?;
+
+        let dirty = &mut self.dirty[page];
+        if start <= dirty.start && dirty.end <= end {
+            *dirty = DirtyColumns::CLEAN;
+        }
+
+        Ok(())
     }
 
-    /// Write out data to display
-    pub async fn flush(&mut self) -> Result<(), DisplayError> {
+    /// Write out the whole buffer, whether it changed or not
+    pub async fn flush_all(&mut self) -> Result<(), Error> {
         // Ensure the display buffer is at the origin of the display before we
         // send the full frame to prevent accidental offsets
         let (display_width, display_height) = DV::dimensions();
@@ -110,13 +280,22 @@ where
         self.properties
             .set_draw_area(
                 (column_offset, 0),
-                (display_width + column_offset, display_height),
+                (
+                    display_width as u8 + column_offset,
+                    (display_height / 8) as u8,
+                ),
             )
             .await?;
 
-        let length = (display_width as usize) * (display_height as usize) / 8;
+        let length =
+            usize::from(display_width) * usize::from(display_height) / 8;
 
-        self.properties.draw(&self.buffer[..length]).await
+        self.properties
+            .draw(&self.buffer.as_ref()[..length])
+            .await?;
+        self.dirty = [DirtyColumns::CLEAN; MAX_PAGES];
+
+        Ok(())
     }
 
     /// Turn a pixel on or off. A non-zero `value` is treated as on, `0` as off.
@@ -124,63 +303,88 @@ where
     /// this method call is a noop.
     pub fn set_pixel(&mut self, x: u32, y: u32, value: u8) {
         let (display_width, _) = DV::dimensions();
-        let display_rotation = self.properties.get_rotation();
-
-        let idx = match display_rotation {
-            DisplayRotation::Rotate0 | DisplayRotation::Rotate180 => {
-                if x >= display_width as u32 {
-                    return;
-                }
-                ((y as usize) / 8 * display_width as usize) + (x as usize)
-            }
-
-            DisplayRotation::Rotate90 | DisplayRotation::Rotate270 => {
-                if y >= display_width as u32 {
-                    return;
-                }
-                ((x as usize) / 8 * display_width as usize) + (y as usize)
-            }
+        let Some((column, row)) = self.buffer_position(x, y) else {
+            return;
         };
 
-        if idx >= self.buffer.len() {
+        let idx =
+            (row as usize) / 8 * usize::from(display_width) + (column as usize);
+        if idx >= self.buffer.as_ref().len() {
             return;
         }
 
-        let (byte, bit) = match display_rotation {
-            DisplayRotation::Rotate0 | DisplayRotation::Rotate180 => {
-                let byte = &mut self.buffer[((y as usize) / 8
-                    * display_width as usize)
-                    + (x as usize)];
-                let bit = 1 << (y % 8);
-
-                (byte, bit)
-            }
-            DisplayRotation::Rotate90 | DisplayRotation::Rotate270 => {
-                let byte = &mut self.buffer[((x as usize) / 8
-                    * display_width as usize)
-                    + (y as usize)];
-                let bit = 1 << (x % 8);
-
-                (byte, bit)
-            }
-        };
-
+        let bit = 1 << (row % 8);
+        let byte = &mut self.buffer.as_mut()[idx];
+        let old = *byte;
         if value == 0 {
             *byte &= !bit;
         } else {
             *byte |= bit;
         }
+
+        if *byte != old {
+            if let Some(dirty) = self.dirty.get_mut(row as usize / 8) {
+                dirty.add(column as u16);
+            }
+        }
+    }
+
+    /// Get whether a pixel is on (`1`) or off (`0`) in the buffer, as drawn
+    /// before the next `flush()`. Returns `None` if the X and Y coordinates are
+    /// out of the bounds of the display.
+    pub fn get_pixel(&self, x: u32, y: u32) -> Option<u8> {
+        let (display_width, _) = DV::dimensions();
+        let (column, row) = self.buffer_position(x, y)?;
+
+        let idx =
+            (row as usize) / 8 * usize::from(display_width) + (column as usize);
+        let byte = self.buffer.as_ref().get(idx)?;
+
+        Some((byte >> (row % 8)) & 1)
+    }
+
+    /// Map display coordinates to the column and row in the buffer. Returns
+    /// `None` if they are out of bounds.
+    fn buffer_position(&self, x: u32, y: u32) -> Option<(u32, u32)> {
+        match self.properties.get_rotation() {
+            // Flipped by the controller, see `DisplayProperties::set_rotation`
+            DisplayRotation::Rotate0 | DisplayRotation::Rotate180 => {
+                DisplayRotation::Rotate0.to_panel(x, y, DV::dimensions())
+            }
+            rotation => rotation.to_panel(x, y, DV::dimensions()),
+        }
     }
 
     /// Display is set up in column mode, i.e. a byte walks down a column of 8
-    /// pixels from column 0 on the left, to column _n_ on the right
-    pub async fn init(&mut self) -> Result<(), DisplayError> {
-        self.properties.init_column_mode().await
+    /// pixels from column 0 on the left, to column _n_ on the right.
+    ///
+    /// Runs the full power-up sequence with the reset and VCC pins given to
+    /// the [`Builder`](crate::Builder).
+    pub async fn init(&mut self) -> Result<(), Error>
+    where
+        RST: OutputPin,
+        VCC: OutputPin,
+        D: PowerDelay<RST, VCC>,
+    {
+        self.properties.init_column_mode().await?;
+        self.mark_all_dirty();
+
+        Ok(())
+    }
+
+    /// Switch the display and its VCC supply off. Call `init()` to power it
+    /// up again.
+    pub async fn power_down(&mut self) -> Result<(), Error>
+    where
+        VCC: OutputPin,
+        D: PowerDelay<RST, VCC>,
+    {
+        self.properties.power_down().await
     }
 
     /// Get display dimensions, taking into account the current rotation of the
     /// display
-    pub fn get_dimensions(&self) -> (u8, u8) {
+    pub fn get_dimensions(&self) -> (u16, u16) {
         self.properties.get_dimensions()
     }
 
@@ -189,45 +393,326 @@ where
         self.properties.get_rotation()
     }
 
-    /// Set the display rotation
+    /// Set the display rotation. The whole buffer is sent again on the next
+    /// flush, as the display RAM layout depends on the rotation.
     pub async fn set_rotation(
         &mut self,
         rot: DisplayRotation,
-    ) -> Result<(), DisplayError> {
-        self.properties.set_rotation(rot).await
+    ) -> Result<(), Error> {
+        self.properties.set_rotation(rot).await?;
+        self.mark_all_dirty();
+
+        Ok(())
+    }
+
+    /// Get whether the image is mirrored horizontally and vertically
+    pub fn get_mirror(&self) -> (bool, bool) {
+        self.properties.get_mirror()
+    }
+
+    /// Mirror the image horizontally and/or vertically, on top of the
+    /// rotation. The whole buffer is sent again on the next flush.
+    pub async fn set_mirror(
+        &mut self,
+        horizontal: bool,
+        vertical: bool,
+    ) -> Result<(), Error> {
+        self.properties.set_mirror(horizontal, vertical).await?;
+        self.mark_all_dirty();
+
+        Ok(())
     }
 
     /// Turn the display on or off. The display can be drawn to and retains all
     /// of its memory even while off.
-    pub async fn display_on(&mut self, on: bool) -> Result<(), DisplayError> {
+    pub async fn display_on(&mut self, on: bool) -> Result<(), Error> {
         self.properties.display_on(on).await
     }
 
     /// Set the display contrast
-    pub async fn set_contrast(
-        &mut self,
-        contrast: u8,
-    ) -> Result<(), DisplayError> {
+    pub async fn set_contrast(&mut self, contrast: u8) -> Result<(), Error> {
         self.properties.set_contrast(contrast).await
     }
+
+    /// Set the RAM row shown on the first row of the panel, cf.
+    /// [`DisplayProperties::set_start_line`]. The buffer is not sent again.
+    pub async fn set_start_line(&mut self, line: u8) -> Result<(), Error> {
+        self.properties.set_start_line(line).await
+    }
+
+    /// Shift the panel rows by `offset`, cf.
+    /// [`DisplayProperties::set_display_offset`]
+    pub async fn set_display_offset(
+        &mut self,
+        offset: u8,
+    ) -> Result<(), Error> {
+        self.properties.set_display_offset(offset).await
+    }
+
+    /// Scroll the image up by `position` rows, or left after a quarter turn,
+    /// wrapping around at the end of the display RAM, cf.
+    /// [`DisplayProperties::set_scroll_position`]. Only the start line
+    /// changes, so this is cheap enough to call every frame.
+    pub async fn set_scroll_position(
+        &mut self,
+        position: u16,
+    ) -> Result<(), Error> {
+        self.properties.set_scroll_position(position).await
+    }
+
+    /// Scroll from position `from` to `to` over `frames` frames, waiting
+    /// `frame_ms` milliseconds between them with the delay given to the
+    /// [`Builder`](crate::Builder)
+    pub async fn animate_scroll(
+        &mut self,
+        from: u16,
+        to: u16,
+        frames: u16,
+        frame_ms: u32,
+    ) -> Result<(), Error>
+    where
+        D: DelayNs,
+    {
+        self.properties
+            .animate_scroll(from, to, frames, frame_ms)
+            .await
+    }
+
+    /// Set up a continuous hardware scroll, cf.
+    /// [`DisplayProperties::setup_scroll`]
+    pub async fn setup_scroll(
+        &mut self,
+        direction: ScrollDirection,
+        start_page: u8,
+        end_page: u8,
+        interval: NFrames,
+        vertical_offset: u8,
+    ) -> Result<(), Error> {
+        self.properties
+            .setup_scroll(
+                direction,
+                start_page,
+                end_page,
+                interval,
+                vertical_offset,
+            )
+            .await
+    }
+
+    /// Start the scroll set up with `setup_scroll`
+    pub async fn start_scroll(&mut self) -> Result<(), Error> {
+        self.properties.start_scroll().await
+    }
+
+    /// Stop a running scroll. The whole buffer is sent again on the next flush,
+    /// as scrolling moves the contents of the display RAM.
+    pub async fn stop_scroll(&mut self) -> Result<(), Error> {
+        self.properties.stop_scroll().await?;
+        self.mark_all_dirty();
+
+        Ok(())
+    }
 }
 
 #[cfg(feature = "graphics")]
-use embedded_graphics_core::{
-    draw_target::DrawTarget,
-    geometry::{Dimensions, OriginDimensions, Size},
-    pixelcolor::BinaryColor,
-    Pixel,
-};
+impl<DV, DI, RST, VCC, D> GraphicsMode<DV, DI, RST, VCC, D>
+where
+    DI: WriteOnlyDataCommand,
+    DV: MonochromeVariant,
+{
+    /// Map `area`, given in display coordinates, to the columns and rows it
+    /// covers in the buffer. Returns `None` if it is outside the display.
+    fn panel_area(
+        &self,
+        area: &Rectangle,
+    ) -> Option<(RangeInclusive<u32>, RangeInclusive<u32>)> {
+        let area = area.intersection(&self.bounding_box());
+        let bottom_right = area.bottom_right()?;
+
+        // Opposite corners stay opposite under rotation
+        let (x0, y0) = self
+            .buffer_position(area.top_left.x as u32, area.top_left.y as u32)?;
+        let (x1, y1) =
+            self.buffer_position(bottom_right.x as u32, bottom_right.y as u32)?;
+
+        Some((x0.min(x1)..=x0.max(x1), y0.min(y1)..=y0.max(y1)))
+    }
+
+    /// Set the buffer bits of `rows` in `columns` to `on`, a page byte at a
+    /// time. The ranges are in buffer coordinates and must be on the panel.
+    fn fill_panel(
+        &mut self,
+        columns: RangeInclusive<u32>,
+        rows: RangeInclusive<u32>,
+        on: bool,
+    ) {
+        let (first_row, last_row) = (*rows.start(), *rows.end());
+
+        for page in first_row / 8..=last_row / 8 {
+            // Rows of this page inside `rows`
+            let top = first_row.max(page * 8) % 8;
+            let bottom = last_row.min(page * 8 + 7) % 8;
+            let mask = (0xFF >> (7 - bottom)) & (0xFF << top);
+            let bits = if on { mask } else { 0 };
+
+            for column in columns.clone() {
+                self.write_byte(page as usize, column, mask, bits);
+            }
+        }
+    }
+
+    /// Replace the `mask` bits of the buffer byte at `column` of `page` with
+    /// those of `bits`
+    fn write_byte(&mut self, page: usize, column: u32, mask: u8, bits: u8) {
+        let (display_width, _) = DV::dimensions();
+        let idx = page * usize::from(display_width) + column as usize;
+
+        let byte = &mut self.buffer.as_mut()[idx];
+        let new = (*byte & !mask) | (bits & mask);
+        if new != *byte {
+            *byte = new;
+            self.dirty[page].add(column as u16);
+        }
+    }
+
+    /// Draw `colors`, given row by row for `area` in display coordinates.
+    /// Pixels are gathered into page bytes before they go to the buffer.
+    fn draw_contiguous<I>(&mut self, area: &Rectangle, colors: I)
+    where
+        I: IntoIterator<Item = BinaryColor>,
+    {
+        let visible = area.intersection(&self.bounding_box());
+        let (Some(area_end), Some(visible_end)) =
+            (area.bottom_right(), visible.bottom_right())
+        else {
+            return;
+        };
+        let visible_columns = visible.top_left.x..=visible_end.x;
+        let mut colors = colors.into_iter();
+
+        match self.properties.get_rotation() {
+            // Display rows run along the buffer rows, gather a page of rows
+            DisplayRotation::Rotate0 | DisplayRotation::Rotate180 => {
+                let left = visible.top_left.x;
+                let column = left as u32;
+                let width = visible.size.width as usize;
+                let mut strip = [0u8; MAX_COLUMNS];
+                let mut mask = 0;
+
+                for y in area.top_left.y..=area_end.y {
+                    let row_visible = y >= visible.top_left.y;
+                    let bit = 1 << y.rem_euclid(8);
+                    let mut drawn = width;
+
+                    for x in area.top_left.x..=area_end.x {
+                        let Some(color) = colors.next() else {
+                            drawn = (x - left).clamp(0, width as i32) as usize;
+                            break;
+                        };
+                        if row_visible
+                            && visible_columns.contains(&x)
+                            && color.is_on()
+                        {
+                            strip[(x - left) as usize] |= bit;
+                        }
+                    }
+
+                    if !row_visible {
+                        continue;
+                    }
+                    let page = y as usize / 8;
+                    if drawn < width {
+                        // Out of colors part way through the row
+                        self.write_strip(page, column, &strip[..width], mask);
+                        self.write_strip(page, column, &strip[..drawn], bit);
+                        return;
+                    }
+                    mask |= bit;
+                    if y % 8 == 7 || y == visible_end.y {
+                        self.write_strip(page, column, &strip[..width], mask);
+                        strip[..width].fill(0);
+                        mask = 0;
+                    }
+                    if y == visible_end.y {
+                        return;
+                    }
+                }
+            }
+            // Display rows run down a buffer column, gather a page byte
+            rotation => {
+                let (width, height) = DV::dimensions();
+                let (width, height) = (i32::from(width), i32::from(height));
+
+                for y in area.top_left.y..=area_end.y {
+                    let row_visible = y >= visible.top_left.y;
+                    let column = match rotation {
+                        DisplayRotation::Rotate90 => width - 1 - y,
+                        _ => y,
+                    } as u32;
+                    let mut pending: Option<(usize, u8, u8)> = None;
+                    let mut exhausted = false;
+
+                    for x in area.top_left.x..=area_end.x {
+                        let Some(color) = colors.next() else {
+                            exhausted = true;
+                            break;
+                        };
+                        if !row_visible || !visible_columns.contains(&x) {
+                            continue;
+                        }
+
+                        let row = match rotation {
+                            DisplayRotation::Rotate90 => x,
+                            _ => height - 1 - x,
+                        } as usize;
+                        let (page, bit) = (row / 8, 1 << (row % 8));
+                        let on = if color.is_on() { bit } else { 0 };
+                        pending = match pending {
+                            Some((p, mask, bits)) if p == page => {
+                                Some((page, mask | bit, bits | on))
+                            }
+                            _ => {
+                                if let Some((p, mask, bits)) = pending {
+                                    self.write_byte(p, column, mask, bits);
+                                }
+                                Some((page, bit, on))
+                            }
+                        };
+                    }
+
+                    if let Some((page, mask, bits)) = pending {
+                        self.write_byte(page, column, mask, bits);
+                    }
+                    if exhausted || y == visible_end.y {
+                        return;
+                    }
+                }
+            }
+        }
+    }
+
+    /// Write the `mask` bits of `strip` to `page`, starting at `column`
+    fn write_strip(
+        &mut self,
+        page: usize,
+        column: u32,
+        strip: &[u8],
+        mask: u8,
+    ) {
+        for (offset, &bits) in strip.iter().enumerate() {
+            self.write_byte(page, column + offset as u32, mask, bits);
+        }
+    }
+}
 
 #[cfg(feature = "graphics")]
-impl<DV, DI, const BS: usize> DrawTarget for GraphicsMode<DV, DI, BS>
+impl<DV, DI, RST, VCC, D> DrawTarget for GraphicsMode<DV, DI, RST, VCC, D>
 where
-    DI: AsyncWriteOnlyDataCommand,
-    DV: DisplayVariant,
+    DI: WriteOnlyDataCommand,
+    DV: MonochromeVariant,
 {
     type Color = BinaryColor;
-    type Error = DisplayError;
+    type Error = Error;
 
     fn draw_iter<I>(&mut self, pixels: I) -> Result<(), Self::Error>
     where
@@ -244,13 +729,42 @@ where
 
         Ok(())
     }
+
+    fn fill_contiguous<I>(
+        &mut self,
+        area: &Rectangle,
+        colors: I,
+    ) -> Result<(), Self::Error>
+    where
+        I: IntoIterator<Item = Self::Color>,
+    {
+        self.draw_contiguous(area, colors);
+
+        Ok(())
+    }
+
+    fn fill_solid(
+        &mut self,
+        area: &Rectangle,
+        color: Self::Color,
+    ) -> Result<(), Self::Error> {
+        if let Some((columns, rows)) = self.panel_area(area) {
+            self.fill_panel(columns, rows, color.is_on());
+        }
+
+        Ok(())
+    }
+
+    fn clear(&mut self, color: Self::Color) -> Result<(), Self::Error> {
+        self.fill_solid(&self.bounding_box(), color)
+    }
 }
 
 #[cfg(feature = "graphics")]
-impl<DV, DI, const BS: usize> OriginDimensions for GraphicsMode<DV, DI, BS>
+impl<DV, DI, RST, VCC, D> OriginDimensions for GraphicsMode<DV, DI, RST, VCC, D>
 where
-    DI: AsyncWriteOnlyDataCommand,
-    DV: DisplayVariant,
+    DI: WriteOnlyDataCommand,
+    DV: MonochromeVariant,
 {
     fn size(&self) -> Size {
         let (w, h) = self.get_dimensions();
@@ -258,3 +772,306 @@ where
         Size::new(w.into(), h.into())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    #[cfg(feature = "async")]
+    use display_interface::AsyncWriteOnlyDataCommand as WriteOnlyDataCommand;
+    #[cfg(not(feature = "async"))]
+    use display_interface::WriteOnlyDataCommand;
+
+    extern crate std;
+
+    use std::vec;
+
+    #[cfg(feature = "graphics")]
+    use embedded_graphics_core::{
+        geometry::{Point, Size},
+        primitives::PointsIter,
+    };
+
+    use super::{DelayNs, DirtyColumns, GraphicsMode, MAX_PAGES};
+    use crate::{
+        displayrotation::DisplayRotation,
+        displays::{DisplayVariant, InitConfig, MonochromeVariant},
+        mode::displaymode::DisplayModeTrait,
+        properties::DisplayProperties,
+        test_helpers::{block_on, RecordingInterface, Transfer},
+        Error,
+    };
+    #[cfg(feature = "graphics")]
+    use crate::{
+        displays::{ssd1306::Ssd1306, Screen128x32},
+        mode::graphics::{BinaryColor, DrawTarget, Pixel, Rectangle},
+    };
+
+    /// Small non-square panel, 16 columns by one page
+    struct Wide;
+
+    impl DisplayVariant for Wide {
+        const WIDTH: u16 = 16;
+        const HEIGHT: u16 = 8;
+        type Buffer = [u8; 16];
+    }
+
+    #[maybe_async_cfg::maybe(
+        sync(cfg(not(feature = "async")),),
+        async(feature = "async"),
+        keep_self
+    )]
+    impl MonochromeVariant for Wide {
+        async fn init_column_mode<DI, D>(
+            _iface: &mut DI,
+            _config: &InitConfig,
+            _delay: &mut D,
+        ) -> Result<(), Error>
+        where
+            DI: WriteOnlyDataCommand,
+            D: DelayNs,
+        {
+            Ok(())
+        }
+    }
+
+    fn graphics(
+        rotation: DisplayRotation,
+    ) -> GraphicsMode<Wide, RecordingInterface> {
+        GraphicsMode::new(DisplayProperties::new(
+            Wide,
+            RecordingInterface::new(),
+            rotation,
+        ))
+    }
+
+    /// Draw an "L" of three pixels: the top left corner, two pixels to its
+    /// right and one below, and return the lit panel pixels
+    fn draw_pattern(rotation: DisplayRotation) -> [[bool; 16]; 8] {
+        let mut display = graphics(rotation);
+        display.set_pixel(0, 0, 1);
+        display.set_pixel(2, 0, 1);
+        display.set_pixel(0, 1, 1);
+
+        let mut panel = [[false; 16]; 8];
+        for (y, row) in panel.iter_mut().enumerate() {
+            for (x, pixel) in row.iter_mut().enumerate() {
+                *pixel = display.buffer.as_ref()[x] & (1 << y) != 0;
+            }
+        }
+        panel
+    }
+
+    fn lit(panel: &[[bool; 16]; 8]) -> [(usize, usize); 3] {
+        let mut lit = [(0, 0); 3];
+        let mut count = 0;
+        for (y, row) in panel.iter().enumerate() {
+            for (x, _) in row.iter().enumerate().filter(|(_, on)| **on) {
+                lit[count] = (x, y);
+                count += 1;
+            }
+        }
+        assert_eq!(count, 3);
+        lit
+    }
+
+    #[test]
+    fn rotate0() {
+        let panel = draw_pattern(DisplayRotation::Rotate0);
+        assert_eq!(lit(&panel), [(0, 0), (2, 0), (0, 1)]);
+    }
+
+    #[test]
+    fn rotate90() {
+        // Display top left is the panel's top right, display x runs down
+        let panel = draw_pattern(DisplayRotation::Rotate90);
+        assert_eq!(lit(&panel), [(14, 0), (15, 0), (15, 2)]);
+    }
+
+    #[test]
+    fn rotate180() {
+        // Flipped by the controller, the buffer is unchanged
+        let panel = draw_pattern(DisplayRotation::Rotate180);
+        assert_eq!(lit(&panel), [(0, 0), (2, 0), (0, 1)]);
+    }
+
+    #[test]
+    fn rotate270() {
+        // Display top left is the panel's bottom left, display x runs up
+        let panel = draw_pattern(DisplayRotation::Rotate270);
+        assert_eq!(lit(&panel), [(0, 5), (0, 7), (1, 7)]);
+    }
+
+    #[test]
+    fn quarter_turns_swap_dimensions() {
+        assert_eq!(
+            graphics(DisplayRotation::Rotate0).get_dimensions(),
+            (16, 8)
+        );
+        assert_eq!(
+            graphics(DisplayRotation::Rotate90).get_dimensions(),
+            (8, 16)
+        );
+        assert_eq!(
+            graphics(DisplayRotation::Rotate180).get_dimensions(),
+            (16, 8)
+        );
+        assert_eq!(
+            graphics(DisplayRotation::Rotate270).get_dimensions(),
+            (8, 16)
+        );
+    }
+
+    #[test]
+    fn quarter_turns_clip_to_rotated_size() {
+        let mut display = graphics(DisplayRotation::Rotate90);
+        // In range of the panel but not of the rotated display
+        display.set_pixel(8, 0, 1);
+        display.set_pixel(0, 16, 1);
+        assert!(display.buffer.as_ref().iter().all(|byte| *byte == 0));
+
+        // Bottom right of the rotated display is the panel's bottom left
+        display.set_pixel(7, 15, 1);
+        assert_eq!(display.buffer.as_ref()[0], 0x80);
+    }
+
+    #[test]
+    fn flush_sends_only_changed_columns() {
+        let mut display = graphics(DisplayRotation::Rotate0);
+        display.dirty = [DirtyColumns::CLEAN; MAX_PAGES];
+        display.set_pixel(3, 0, 1);
+        display.set_pixel(5, 1, 1);
+        block_on(display.flush()).unwrap();
+
+        let iface = display.release().release();
+        assert_eq!(
+            iface.transfers,
+            [
+                Transfer::Commands(vec![0xB0, 0x03, 0x10]),
+                Transfer::Data(vec![0x01, 0x00, 0x02]),
+            ]
+        );
+    }
+
+    #[cfg(feature = "graphics")]
+    type Multipage = GraphicsMode<Ssd1306<Screen128x32>, RecordingInterface>;
+
+    /// Display with a diagonal pattern already drawn and nothing to flush
+    #[cfg(feature = "graphics")]
+    fn multipage(rotation: DisplayRotation) -> Multipage {
+        let mut display = GraphicsMode::new(DisplayProperties::new(
+            Ssd1306::<Screen128x32>::new(),
+            RecordingInterface::new(),
+            rotation,
+        ));
+        for (i, byte) in display.buffer.as_mut().iter_mut().enumerate() {
+            *byte = (i as u8).wrapping_mul(37);
+        }
+        display.dirty = [DirtyColumns::CLEAN; MAX_PAGES];
+        display
+    }
+
+    #[cfg(feature = "graphics")]
+    fn pattern() -> impl Iterator<Item = BinaryColor> {
+        (0..).map(|i: u32| {
+            BinaryColor::from(i.is_multiple_of(3) || i.is_multiple_of(7))
+        })
+    }
+
+    /// Areas inside, across the edges of and outside the display
+    #[cfg(feature = "graphics")]
+    const AREAS: [Rectangle; 5] = [
+        Rectangle::new(Point::new(3, 5), Size::new(20, 13)),
+        Rectangle::new(Point::new(-4, -3), Size::new(10, 40)),
+        Rectangle::new(Point::new(20, 9), Size::new(200, 1)),
+        Rectangle::new(Point::new(0, 0), Size::new(128, 128)),
+        Rectangle::new(Point::new(-10, 4), Size::new(5, 5)),
+    ];
+
+    /// Draw `colors` into `area` with `fill` and pixel by pixel, and check the
+    /// buffers and dirty columns end up the same
+    #[cfg(feature = "graphics")]
+    fn assert_fill_matches_pixels<C>(
+        fill: impl Fn(&mut Multipage, &Rectangle),
+        colors: impl Fn() -> C,
+    ) where
+        C: Iterator<Item = BinaryColor>,
+    {
+        for rotation in [
+            DisplayRotation::Rotate0,
+            DisplayRotation::Rotate90,
+            DisplayRotation::Rotate180,
+            DisplayRotation::Rotate270,
+        ] {
+            for area in AREAS {
+                let mut filled = multipage(rotation);
+                fill(&mut filled, &area);
+
+                let mut drawn = multipage(rotation);
+                drawn
+                    .draw_iter(
+                        area.points().zip(colors()).map(|(p, c)| Pixel(p, c)),
+                    )
+                    .unwrap();
+
+                assert!(
+                    filled.buffer == drawn.buffer,
+                    "rotation {} {area:?}",
+                    rotation as u8
+                );
+                for (a, b) in filled.dirty.iter().zip(drawn.dirty.iter()) {
+                    assert_eq!(
+                        (a.is_clean(), a.start, a.end),
+                        (b.is_clean(), b.start, b.end),
+                        "rotation {} {area:?}",
+                        rotation as u8
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "graphics")]
+    fn fill_solid_matches_pixels() {
+        for color in [BinaryColor::On, BinaryColor::Off] {
+            assert_fill_matches_pixels(
+                |display, area| display.fill_solid(area, color).unwrap(),
+                || core::iter::repeat(color),
+            );
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "graphics")]
+    fn fill_contiguous_matches_pixels() {
+        assert_fill_matches_pixels(
+            |display, area| display.fill_contiguous(area, pattern()).unwrap(),
+            pattern,
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "graphics")]
+    fn fill_contiguous_stops_when_out_of_colors() {
+        for count in [0, 1, 25, 97] {
+            assert_fill_matches_pixels(
+                |display, area| {
+                    display
+                        .fill_contiguous(area, pattern().take(count))
+                        .unwrap()
+                },
+                || pattern().take(count),
+            );
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "graphics")]
+    fn clear_fills_the_whole_display() {
+        let mut display = multipage(DisplayRotation::Rotate90);
+        DrawTarget::clear(&mut display, BinaryColor::On).unwrap();
+        assert!(display.buffer.as_ref().iter().all(|byte| *byte == 0xFF));
+
+        DrawTarget::clear(&mut display, BinaryColor::Off).unwrap();
+        assert!(display.buffer.as_ref().iter().all(|byte| *byte == 0));
+    }
+}
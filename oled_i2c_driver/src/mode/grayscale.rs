@@ -0,0 +1,407 @@
+//! Buffered 4-bit grayscale display module for use with the
+//! [embedded-graphics] crate
+//!
+//! Works with the window addressed controllers implementing
+//! [`GrayscaleVariant`]. Rotation and mirroring are applied in software when
+//! drawing, so all combinations work on any panel.
+#![cfg_attr(
+    all(feature = "async", feature = "graphics"),
+    doc = "```rust,no_run"
+)]
+#![cfg_attr(
+    not(all(feature = "async", feature = "graphics")),
+    doc = "```rust,ignore"
+)]
+//! use embedded_graphics::{
+//!     pixelcolor::Gray4,
+//!     prelude::*,
+//!     primitives::{Circle, PrimitiveStyle},
+//! };
+//! # use display_interface::AsyncWriteOnlyDataCommand;
+//! use oled_i2c_driver::{
+//!     displays::{ssd1327::Ssd1327, Screen128x128},
+//!     mode::GrayscaleMode,
+//!     Builder,
+//! };
+//!
+//! # async fn run(display_interface: impl AsyncWriteOnlyDataCommand) {
+//! let mut disp: GrayscaleMode<_, _> =
+//!     Builder::new(Ssd1327::<Screen128x128>::new())
+//!         .connect(display_interface)
+//!         .into();
+//!
+//! disp.init().await.unwrap();
+//! disp.clear();
+//!
+//! Circle::new(Point::new(8, 8), 48)
+//!     .into_styled(PrimitiveStyle::with_fill(Gray4::new(9)))
+//!     .draw(&mut disp)
+//!     .unwrap();
+//!
+//! disp.flush().await.unwrap();
+//! # }
+//! ```
+
+#[cfg(feature = "async")]
+use display_interface::AsyncWriteOnlyDataCommand as WriteOnlyDataCommand;
+#[cfg(not(feature = "async"))]
+use display_interface::WriteOnlyDataCommand;
+#[cfg(feature = "graphics")]
+use embedded_graphics_core::{
+    draw_target::DrawTarget,
+    geometry::{OriginDimensions, Size},
+    pixelcolor::{Gray4, GrayColor},
+    Pixel,
+};
+use hal::digital::OutputPin;
+
+use crate::{
+    builder::{NoDelay, NoOutputPin, PowerDelay},
+    displayrotation::DisplayRotation,
+    displays::{Framebuffer, GrayscaleVariant},
+    mode::displaymode::DisplayModeTrait,
+    properties::DisplayProperties,
+    Error,
+};
+
+/// Grayscale graphics mode handler
+pub struct GrayscaleMode<
+    DV,
+    DI,
+    RST = NoOutputPin,
+    VCC = NoOutputPin,
+    D = NoDelay,
+> where
+    DI: WriteOnlyDataCommand,
+    DV: GrayscaleVariant,
+{
+    properties: DisplayProperties<DV, DI, RST, VCC, D>,
+    buffer: DV::Buffer,
+}
+
+impl<DV, DI, RST, VCC, D> DisplayModeTrait<DV, DI, RST, VCC, D>
+    for GrayscaleMode<DV, DI, RST, VCC, D>
+where
+    DI: WriteOnlyDataCommand,
+    DV: GrayscaleVariant,
+{
+    /// Create new GrayscaleMode instance
+    fn new(properties: DisplayProperties<DV, DI, RST, VCC, D>) -> Self {
+        const {
+            assert!(
+                DV::Buffer::LEN * 2 >= DV::WIDTH as usize * DV::HEIGHT as usize,
+                "the variant's buffer is too small for four bits per pixel"
+            )
+        };
+
+        GrayscaleMode {
+            properties,
+            buffer: DV::Buffer::ZEROED,
+        }
+    }
+
+    /// Release all resources used by GrayscaleMode
+    fn release(self) -> DisplayProperties<DV, DI, RST, VCC, D> {
+        self.properties
+    }
+}
+
+#[maybe_async_cfg::maybe(
+    sync(cfg(not(feature = "async")),),
+    async(feature = "async"),
+    keep_self
+)]
+impl<DV, DI, RST, VCC, D> GrayscaleMode<DV, DI, RST, VCC, D>
+where
+    DI: WriteOnlyDataCommand,
+    DV: GrayscaleVariant,
+{
+    /// Clear the display buffer. You need to call `display.flush()` for any
+    /// effect on the screen
+    pub fn clear(&mut self) {
+        self.buffer = DV::Buffer::ZEROED;
+    }
+
+    /// Write out data to display
+    pub async fn flush(&mut self) -> Result<(), Error> {
+        let (display_width, display_height) = DV::dimensions();
+        let length =
+            usize::from(display_width) * usize::from(display_height) / 2;
+
+        self.properties
+            .draw_window(
+                (0, display_width),
+                (0, display_height),
+                &self.buffer.as_ref()[..length],
+            )
+            .await
+    }
+
+    /// Set the gray level of a pixel, from `0` (off) to `15` (full
+    /// brightness). Higher bits of `value` are ignored. If the X and Y
+    /// coordinates are out of the bounds of the display, this method call is
+    /// a noop.
+    pub fn set_pixel(&mut self, x: u32, y: u32, value: u8) {
+        let (display_width, _) = DV::dimensions();
+        let (width, height) = self.get_dimensions();
+        if x >= u32::from(width) || y >= u32::from(height) {
+            return;
+        }
+
+        // These controllers are not flipped in hardware, mirror in software
+        let (mirror_horizontal, mirror_vertical) = self.properties.get_mirror();
+        let x = if mirror_horizontal {
+            u32::from(width) - 1 - x
+        } else {
+            x
+        };
+        let y = if mirror_vertical {
+            u32::from(height) - 1 - y
+        } else {
+            y
+        };
+
+        let Some((x, y)) = self.get_rotation().to_panel(x, y, DV::dimensions())
+        else {
+            return;
+        };
+
+        let idx = (y as usize * usize::from(display_width) + x as usize) / 2;
+        let Some(byte) = self.buffer.as_mut().get_mut(idx) else {
+            return;
+        };
+
+        // The left pixel of each pair lives in the high nibble
+        let shift = if x % 2 == 0 { 4 } else { 0 };
+        *byte = (*byte & !(0xF << shift)) | ((value & 0xF) << shift);
+    }
+
+    /// Run the power-up sequence with the variant's initialisation commands
+    pub async fn init(&mut self) -> Result<(), Error>
+    where
+        RST: OutputPin,
+        VCC: OutputPin,
+        D: PowerDelay<RST, VCC>,
+    {
+        self.properties.init_grayscale().await
+    }
+
+    /// Switch the display and its VCC supply off. Call `init()` to power it
+    /// up again.
+    pub async fn power_down(&mut self) -> Result<(), Error>
+    where
+        VCC: OutputPin,
+        D: PowerDelay<RST, VCC>,
+    {
+        self.properties.power_down().await
+    }
+
+    /// Get display dimensions, taking into account the current rotation of the
+    /// display
+    pub fn get_dimensions(&self) -> (u16, u16) {
+        self.properties.get_dimensions()
+    }
+
+    /// Get the display rotation
+    pub fn get_rotation(&self) -> DisplayRotation {
+        self.properties.get_rotation()
+    }
+
+    /// Get whether the image is mirrored horizontally and vertically
+    pub fn get_mirror(&self) -> (bool, bool) {
+        self.properties.get_mirror()
+    }
+
+    /// Turn the display on or off. The display can be drawn to and retains all
+    /// of its memory even while off.
+    pub async fn display_on(&mut self, on: bool) -> Result<(), Error> {
+        self.properties.display_on(on).await
+    }
+}
+
+#[cfg(feature = "graphics")]
+impl<DV, DI, RST, VCC, D> DrawTarget for GrayscaleMode<DV, DI, RST, VCC, D>
+where
+    DI: WriteOnlyDataCommand,
+    DV: GrayscaleVariant,
+{
+    type Color = Gray4;
+    type Error = Error;
+
+    fn draw_iter<I>(&mut self, pixels: I) -> Result<(), Self::Error>
+    where
+        I: IntoIterator<Item = Pixel<Self::Color>>,
+    {
+        pixels
+            .into_iter()
+            .filter(|Pixel(pos, _color)| pos.x >= 0 && pos.y >= 0)
+            .for_each(|Pixel(pos, color)| {
+                self.set_pixel(pos.x as u32, pos.y as u32, color.luma())
+            });
+
+        Ok(())
+    }
+}
+
+#[cfg(feature = "graphics")]
+impl<DV, DI, RST, VCC, D> OriginDimensions
+    for GrayscaleMode<DV, DI, RST, VCC, D>
+where
+    DI: WriteOnlyDataCommand,
+    DV: GrayscaleVariant,
+{
+    fn size(&self) -> Size {
+        let (w, h) = self.get_dimensions();
+
+        Size::new(w.into(), h.into())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate std;
+
+    use std::vec;
+
+    use super::GrayscaleMode;
+    use crate::{
+        displayrotation::DisplayRotation,
+        displays::{
+            sh1122::Sh1122,
+            ssd1327::Ssd1327,
+            Screen128x128,
+            Screen256x64,
+        },
+        mode::displaymode::DisplayModeTrait,
+        properties::DisplayProperties,
+        test_helpers::{block_on, RecordingInterface, Transfer},
+    };
+
+    type Square = GrayscaleMode<Ssd1327<Screen128x128>, RecordingInterface>;
+
+    fn grayscale(rotation: DisplayRotation, mirror: (bool, bool)) -> Square {
+        GrayscaleMode::new(
+            DisplayProperties::new(
+                Ssd1327::new(),
+                RecordingInterface::new(),
+                rotation,
+            )
+            .with_mirror(mirror.0, mirror.1),
+        )
+    }
+
+    /// Gray level of the pixel at panel coordinates `x`, `y`
+    fn panel_pixel(display: &Square, x: usize, y: usize) -> u8 {
+        let byte = display.buffer[(y * 128 + x) / 2];
+        if x % 2 == 1 {
+            byte & 0xF
+        } else {
+            byte >> 4
+        }
+    }
+
+    /// Set the display's top left pixel and return where it lands on the panel
+    fn origin_on_panel(
+        rotation: DisplayRotation,
+        mirror: (bool, bool),
+    ) -> (usize, usize) {
+        let mut display = grayscale(rotation, mirror);
+        display.set_pixel(0, 0, 0xF);
+
+        let index = display.buffer.iter().position(|byte| *byte != 0).unwrap();
+        let x = index % 64 * 2 + usize::from(display.buffer[index] == 0x0F);
+        assert_eq!(panel_pixel(&display, x, index / 64), 0xF);
+        (x, index / 64)
+    }
+
+    #[test]
+    fn left_pixel_in_high_nibble() {
+        let mut display = grayscale(DisplayRotation::Rotate0, (false, false));
+        display.set_pixel(0, 0, 0xA);
+        display.set_pixel(1, 0, 0x5);
+        display.set_pixel(3, 1, 0x7);
+        assert_eq!(display.buffer[0], 0xA5);
+        assert_eq!(display.buffer[64 + 1], 0x07);
+
+        // Overwriting one pixel keeps its neighbour, extra bits are dropped
+        display.set_pixel(0, 0, 0x13);
+        assert_eq!(display.buffer[0], 0x35);
+    }
+
+    #[test]
+    fn out_of_bounds_is_ignored() {
+        let mut display = grayscale(DisplayRotation::Rotate90, (false, false));
+        display.set_pixel(128, 0, 0xF);
+        display.set_pixel(0, 128, 0xF);
+        assert!(display.buffer.iter().all(|byte| *byte == 0));
+    }
+
+    #[test]
+    fn rotation_in_software() {
+        use DisplayRotation::*;
+
+        let none = (false, false);
+        assert_eq!(origin_on_panel(Rotate0, none), (0, 0));
+        assert_eq!(origin_on_panel(Rotate90, none), (127, 0));
+        assert_eq!(origin_on_panel(Rotate180, none), (127, 127));
+        assert_eq!(origin_on_panel(Rotate270, none), (0, 127));
+    }
+
+    #[test]
+    fn mirroring_in_software() {
+        use DisplayRotation::*;
+
+        assert_eq!(origin_on_panel(Rotate0, (true, false)), (127, 0));
+        assert_eq!(origin_on_panel(Rotate0, (false, true)), (0, 127));
+        assert_eq!(origin_on_panel(Rotate0, (true, true)), (127, 127));
+        // Mirroring applies to the image as seen after rotation
+        assert_eq!(origin_on_panel(Rotate90, (true, false)), (127, 127));
+        assert_eq!(origin_on_panel(Rotate90, (false, true)), (0, 0));
+    }
+
+    #[test]
+    fn flush_sends_whole_window() {
+        let mut display = grayscale(DisplayRotation::Rotate0, (false, false));
+        display.set_pixel(0, 0, 0x1);
+        display.set_pixel(127, 127, 0x2);
+        block_on(display.flush()).unwrap();
+
+        let mut data = vec![0; 128 * 128 / 2];
+        data[0] = 0x10;
+        data[128 * 128 / 2 - 1] = 0x02;
+        let iface = display.release().release();
+        assert_eq!(
+            iface.transfers,
+            [
+                Transfer::Commands(vec![0x15, 0, 63, 0x75, 0, 127]),
+                Transfer::Data(data),
+            ]
+        );
+    }
+
+    #[test]
+    fn flush_sets_window_per_row_if_not_wrapping() {
+        let mut display: GrayscaleMode<Sh1122<Screen256x64>, _> =
+            GrayscaleMode::new(DisplayProperties::new(
+                Sh1122::new(),
+                RecordingInterface::new(),
+                DisplayRotation::Rotate0,
+            ));
+        display.set_pixel(2, 1, 0xC);
+        block_on(display.flush()).unwrap();
+
+        let iface = display.release().release();
+        let data = iface
+            .transfers
+            .iter()
+            .filter_map(|transfer| match transfer {
+                Transfer::Data(bytes) => Some(bytes),
+                Transfer::Commands(_) => None,
+            })
+            .collect::<vec::Vec<_>>();
+        assert_eq!(data.len(), 64);
+        assert!(data.iter().all(|row| row.len() == 128));
+        assert_eq!(data[1][1], 0xC0);
+        assert_eq!(iface.transfers.len(), 2 * 64);
+    }
+}
@@ -6,6 +6,15 @@
 
 pub mod displaymode;
 pub mod graphics;
+pub mod grayscale;
 pub mod raw;
+pub mod terminal;
+#[cfg(test)]
+mod tests;
 
-pub use self::{graphics::GraphicsMode, raw::RawMode};
+pub use self::{
+    graphics::GraphicsMode,
+    grayscale::GrayscaleMode,
+    raw::RawMode,
+    terminal::TerminalMode,
+};
@@ -1,40 +1,45 @@
-use display_interface::AsyncWriteOnlyDataCommand;
+#[cfg(feature = "async")]
+use display_interface::AsyncWriteOnlyDataCommand as WriteOnlyDataCommand;
+#[cfg(not(feature = "async"))]
+use display_interface::WriteOnlyDataCommand;
 
 use crate::{
+    builder::{NoDelay, NoOutputPin},
     displays::DisplayVariant,
     mode::displaymode::DisplayModeTrait,
     properties::DisplayProperties,
 };
 
 /// Raw display mode
-pub struct RawMode<DV, DI>
+pub struct RawMode<DV, DI, RST = NoOutputPin, VCC = NoOutputPin, D = NoDelay>
 where
-    DI: AsyncWriteOnlyDataCommand,
+    DI: WriteOnlyDataCommand,
 {
-    properties: DisplayProperties<DV, DI>,
+    properties: DisplayProperties<DV, DI, RST, VCC, D>,
 }
 
-impl<DV, DI> DisplayModeTrait<DV, DI> for RawMode<DV, DI>
+impl<DV, DI, RST, VCC, D> DisplayModeTrait<DV, DI, RST, VCC, D>
+    for RawMode<DV, DI, RST, VCC, D>
 where
-    DI: AsyncWriteOnlyDataCommand,
+    DI: WriteOnlyDataCommand,
 {
     /// Create new RawMode instance
-    fn new(properties: DisplayProperties<DV, DI>) -> Self {
+    fn new(properties: DisplayProperties<DV, DI, RST, VCC, D>) -> Self {
         RawMode { properties }
     }
 
     /// Release all resources used by RawMode
-    fn release(self) -> DisplayProperties<DV, DI> {
+    fn release(self) -> DisplayProperties<DV, DI, RST, VCC, D> {
         self.properties
     }
 }
 
-impl<DV, DI: AsyncWriteOnlyDataCommand> RawMode<DV, DI>
+impl<DV, DI: WriteOnlyDataCommand, RST, VCC, D> RawMode<DV, DI, RST, VCC, D>
 where
     DV: DisplayVariant,
 {
     /// Create a new raw display mode
-    pub fn new(properties: DisplayProperties<DV, DI>) -> Self {
+    pub fn new(properties: DisplayProperties<DV, DI, RST, VCC, D>) -> Self {
         RawMode { properties }
     }
 }
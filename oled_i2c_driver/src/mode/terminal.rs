@@ -0,0 +1,664 @@
+//! Text terminal display module
+//!
+//! Prints text with a built-in 8x8 pixel font on a grid of character cells,
+//! without needing the embedded-graphics crate. Text wraps at the end of a
+//! line and scrolls up when it runs past the last one. Only the cells that
+//! changed are sent on the next flush.
+#![cfg_attr(feature = "async", doc = "```rust,no_run")]
+#![cfg_attr(not(feature = "async"), doc = "```rust,ignore")]
+//! use core::fmt::Write;
+//!
+//! # use display_interface::AsyncWriteOnlyDataCommand;
+//! use oled_i2c_driver::{
+//!     displays::{sh1106::Sh1106, Screen128x64},
+//!     prelude::*,
+//!     Builder,
+//! };
+//!
+//! async fn run_display(display_interface: impl AsyncWriteOnlyDataCommand) {
+//!     let mut disp: TerminalMode<_, _> =
+//!         Builder::new(Sh1106::<Screen128x64>::new())
+//!             .connect(display_interface)
+//!             .into();
+//!
+//!     disp.init().await.unwrap();
+//!     disp.flush().await.unwrap();
+//!
+//!     writeln!(disp, "Uptime: {}s", 42).unwrap();
+//!     disp.flush().await.unwrap();
+//! }
+//! ```
+
+use core::fmt;
+
+#[cfg(feature = "async")]
+use display_interface::AsyncWriteOnlyDataCommand as WriteOnlyDataCommand;
+#[cfg(not(feature = "async"))]
+use display_interface::WriteOnlyDataCommand;
+use hal::digital::OutputPin;
+
+use crate::{
+    builder::{NoDelay, NoOutputPin, PowerDelay},
+    command::{NFrames, ScrollDirection},
+    displayrotation::DisplayRotation,
+    displays::{Framebuffer, MonochromeVariant},
+    mode::displaymode::DisplayModeTrait,
+    properties::DisplayProperties,
+    Error,
+};
+
+/// Width and height of a character cell in pixels
+const CELL_SIZE: u16 = 8;
+
+/// Most character rows or columns, i.e. 256 pixels
+const MAX_CELLS: usize = 32;
+
+/// Text terminal mode handler
+///
+/// The characters are kept in the variant's framebuffer type, one byte per
+/// cell, so this mode needs no more memory than
+/// [`GraphicsMode`](crate::mode::GraphicsMode).
+pub struct TerminalMode<
+    DV,
+    DI,
+    RST = NoOutputPin,
+    VCC = NoOutputPin,
+    D = NoDelay,
+> where
+    DI: WriteOnlyDataCommand,
+    DV: MonochromeVariant,
+{
+    properties: DisplayProperties<DV, DI, RST, VCC, D>,
+    cells: DV::Buffer,
+    /// One bit per column of every row, set if the cell changed
+    dirty: [u32; MAX_CELLS],
+    cursor: (u16, u16),
+}
+
+impl<DV, DI, RST, VCC, D> DisplayModeTrait<DV, DI, RST, VCC, D>
+    for TerminalMode<DV, DI, RST, VCC, D>
+where
+    DI: WriteOnlyDataCommand,
+    DV: MonochromeVariant,
+{
+    /// Create new TerminalMode instance
+    fn new(properties: DisplayProperties<DV, DI, RST, VCC, D>) -> Self {
+        const {
+            assert!(
+                DV::WIDTH as usize <= MAX_CELLS * CELL_SIZE as usize
+                    && DV::HEIGHT as usize <= MAX_CELLS * CELL_SIZE as usize,
+                "the panel is too large for the terminal mode"
+            );
+            // Either side can become the row after a quarter turn
+            assert!(
+                DV::WIDTH >= CELL_SIZE && DV::HEIGHT >= CELL_SIZE,
+                "the panel is too small for one character cell"
+            )
+        };
+
+        let mut mode = TerminalMode {
+            properties,
+            cells: DV::Buffer::ZEROED,
+            dirty: [0; MAX_CELLS],
+            cursor: (0, 0),
+        };
+        // Nothing is known about the display RAM contents yet
+        mode.clear();
+        mode
+    }
+
+    /// Release all resources used by TerminalMode
+    fn release(self) -> DisplayProperties<DV, DI, RST, VCC, D> {
+        self.properties
+    }
+}
+
+#[maybe_async_cfg::maybe(
+    sync(cfg(not(feature = "async")),),
+    async(feature = "async"),
+    keep_self
+)]
+impl<DV, DI, RST, VCC, D> TerminalMode<DV, DI, RST, VCC, D>
+where
+    DI: WriteOnlyDataCommand,
+    DV: MonochromeVariant,
+{
+    /// Clear the screen and move the cursor to the top left cell. You need to
+    /// call `display.flush()` for any effect on the screen
+    pub fn clear(&mut self) {
+        let (columns, rows) = self.get_size();
+        let cells = usize::from(columns) * usize::from(rows);
+        self.cells.as_mut()[..cells].fill(b' ');
+        self.cursor = (0, 0);
+        self.mark_all_dirty();
+    }
+
+    fn mark_all_dirty(&mut self) {
+        let (columns, rows) = self.get_size();
+        let mask = u32::MAX >> (MAX_CELLS - usize::from(columns));
+        for row in self.dirty[..usize::from(rows)].iter_mut() {
+            *row = mask;
+        }
+    }
+
+    /// Write out the character cells that changed since the last flush
+    pub async fn flush(&mut self) -> Result<(), Error> {
+        let (_, rows) = self.get_size();
+
+        for row in 0..rows {
+            while self.dirty[usize::from(row)] != 0 {
+                let column = self.dirty[usize::from(row)].trailing_zeros();
+                self.flush_cell(column as u16, row).await?;
+                self.dirty[usize::from(row)] &= !(1 << column);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Render the glyph of a cell and send it to the display
+    async fn flush_cell(&mut self, column: u16, row: u16) -> Result<(), Error> {
+        let (columns, _) = self.get_size();
+        let (x, y) =
+            (u32::from(column * CELL_SIZE), u32::from(row * CELL_SIZE));
+
+        // Cells are aligned to pages, so the whole glyph lands in the panel
+        // cell of its top left pixel
+        let Some((panel_x, panel_y)) = self.panel_position(x, y) else {
+            return Ok(());
+        };
+
+        let character = self.cells.as_ref()
+            [usize::from(row) * usize::from(columns) + usize::from(column)];
+        let glyph = &FONT_8X8[usize::from(character - b' ')];
+
+        let mut data = [0; CELL_SIZE as usize];
+        for (glyph_x, bits) in (0..).zip(glyph) {
+            for glyph_y in (0..8).filter(|glyph_y| bits & (1 << glyph_y) != 0) {
+                if let Some((px, py)) =
+                    self.panel_position(x + glyph_x, y + glyph_y)
+                {
+                    data[px as usize % 8] |= 1 << (py % 8);
+                }
+            }
+        }
+
+        let start = (panel_x - panel_x % 8) as u8 + DV::COLUMN_OFFSET;
+        let page = (panel_y / 8) as u8;
+        self.properties
+            .set_draw_area((start, page), (start + 8, page + 1))
+            .await?;
+        self.properties.draw(&data).await
+    }
+
+    /// Map display coordinates to the column and row on the panel. Returns
+    /// `None` if they are out of bounds.
+    fn panel_position(&self, x: u32, y: u32) -> Option<(u32, u32)> {
+        match self.properties.get_rotation() {
+            // Flipped by the controller, see `DisplayProperties::set_rotation`
+            DisplayRotation::Rotate0 | DisplayRotation::Rotate180 => {
+                DisplayRotation::Rotate0.to_panel(x, y, DV::dimensions())
+            }
+            rotation => rotation.to_panel(x, y, DV::dimensions()),
+        }
+    }
+
+    /// Print a character at the cursor and advance it. `'\n'` moves to the
+    /// start of the next line and `'\r'` to the start of the current one.
+    /// Characters outside of printable ASCII are shown as `'?'`.
+    pub fn print_char(&mut self, c: char) {
+        match c {
+            '\n' => self.new_line(),
+            '\r' => self.cursor.0 = 0,
+            c => {
+                let (columns, _) = self.get_size();
+                // Wrap only once there is something to print on the next line
+                if self.cursor.0 >= columns {
+                    self.new_line();
+                }
+
+                let character = match c {
+                    ' '..='~' => c as u8,
+                    _ => b'?',
+                };
+                self.set_cell(self.cursor.0, self.cursor.1, character);
+                self.cursor.0 += 1;
+            }
+        }
+    }
+
+    /// Print a string at the cursor, cf. [`print_char`](Self::print_char)
+    pub fn print(&mut self, s: &str) {
+        for c in s.chars() {
+            self.print_char(c);
+        }
+    }
+
+    fn new_line(&mut self) {
+        let (_, rows) = self.get_size();
+
+        self.cursor.0 = 0;
+        if self.cursor.1 + 1 < rows {
+            self.cursor.1 += 1;
+        } else {
+            self.scroll_up();
+        }
+    }
+
+    /// Move every line up by one, leaving the last one empty
+    fn scroll_up(&mut self) {
+        let (columns, rows) = self.get_size();
+
+        for row in 1..rows {
+            for column in 0..columns {
+                let character = self.cells.as_ref()[usize::from(row)
+                    * usize::from(columns)
+                    + usize::from(column)];
+                self.set_cell(column, row - 1, character);
+            }
+        }
+        for column in 0..columns {
+            self.set_cell(column, rows - 1, b' ');
+        }
+    }
+
+    fn set_cell(&mut self, column: u16, row: u16, character: u8) {
+        let (columns, _) = self.get_size();
+        let cell = &mut self.cells.as_mut()
+            [usize::from(row) * usize::from(columns) + usize::from(column)];
+
+        if *cell != character {
+            *cell = character;
+            self.dirty[usize::from(row)] |= 1 << column;
+        }
+    }
+
+    /// Get the cursor position as column and row. The column is one past the
+    /// last one after printing to the end of a line, the next character then
+    /// wraps to a new line.
+    pub fn get_position(&self) -> (u16, u16) {
+        self.cursor
+    }
+
+    /// Move the cursor to the cell at `column` and `row`
+    pub fn set_position(&mut self, column: u16, row: u16) -> Result<(), Error> {
+        let (columns, rows) = self.get_size();
+        if column >= columns || row >= rows {
+            return Err(Error::OutOfBounds);
+        }
+
+        self.cursor = (column, row);
+        Ok(())
+    }
+
+    /// Get the number of character columns and rows, taking into account the
+    /// current rotation of the display
+    pub fn get_size(&self) -> (u16, u16) {
+        let (width, height) = self.get_dimensions();
+
+        (width / CELL_SIZE, height / CELL_SIZE)
+    }
+
+    /// Display is set up in column mode, i.e. a byte walks down a column of 8
+    /// pixels from column 0 on the left, to column _n_ on the right.
+    ///
+    /// Runs the full power-up sequence with the reset and VCC pins given to
+    /// the [`Builder`](crate::Builder).
+    pub async fn init(&mut self) -> Result<(), Error>
+    where
+        RST: OutputPin,
+        VCC: OutputPin,
+        D: PowerDelay<RST, VCC>,
+    {
+        self.properties.init_column_mode().await?;
+        self.mark_all_dirty();
+
+        Ok(())
+    }
+
+    /// Switch the display and its VCC supply off. Call `init()` to power it
+    /// up again.
+    pub async fn power_down(&mut self) -> Result<(), Error>
+    where
+        VCC: OutputPin,
+        D: PowerDelay<RST, VCC>,
+    {
+        self.properties.power_down().await
+    }
+
+    /// Get display dimensions in pixels, taking into account the current
+    /// rotation of the display
+    pub fn get_dimensions(&self) -> (u16, u16) {
+        self.properties.get_dimensions()
+    }
+
+    /// Get the display rotation
+    pub fn get_rotation(&self) -> DisplayRotation {
+        self.properties.get_rotation()
+    }
+
+    /// Set the display rotation. Quarter turns change the number of columns
+    /// and rows, so the screen is cleared.
+    pub async fn set_rotation(
+        &mut self,
+        rot: DisplayRotation,
+    ) -> Result<(), Error> {
+        self.properties.set_rotation(rot).await?;
+        self.clear();
+
+        Ok(())
+    }
+
+    /// Turn the display on or off. The display can be drawn to and retains all
+    /// of its memory even while off.
+    pub async fn display_on(&mut self, on: bool) -> Result<(), Error> {
+        self.properties.display_on(on).await
+    }
+
+    /// Set the display contrast
+    pub async fn set_contrast(&mut self, contrast: u8) -> Result<(), Error> {
+        self.properties.set_contrast(contrast).await
+    }
+
+    /// Set up a continuous hardware scroll, cf.
+    /// [`DisplayProperties::setup_scroll`]
+    pub async fn setup_scroll(
+        &mut self,
+        direction: ScrollDirection,
+        start_page: u8,
+        end_page: u8,
+        interval: NFrames,
+        vertical_offset: u8,
+    ) -> Result<(), Error> {
+        self.properties
+            .setup_scroll(
+                direction,
+                start_page,
+                end_page,
+                interval,
+                vertical_offset,
+            )
+            .await
+    }
+
+    /// Start the scroll set up with `setup_scroll`
+    pub async fn start_scroll(&mut self) -> Result<(), Error> {
+        self.properties.start_scroll().await
+    }
+
+    /// Stop a running scroll. The whole text is sent again on the next flush,
+    /// as scrolling moves the contents of the display RAM.
+    pub async fn stop_scroll(&mut self) -> Result<(), Error> {
+        self.properties.stop_scroll().await?;
+        self.mark_all_dirty();
+
+        Ok(())
+    }
+}
+
+impl<DV, DI, RST, VCC, D> fmt::Write for TerminalMode<DV, DI, RST, VCC, D>
+where
+    DI: WriteOnlyDataCommand,
+    DV: MonochromeVariant,
+{
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        self.print(s);
+
+        Ok(())
+    }
+}
+
+/// Glyphs for ASCII `0x20..=0x7E`, one byte per column from left to right
+/// with the least significant bit at the top. Based on the public domain
+/// IBM PC BIOS font.
+#[rustfmt::skip]
+const FONT_8X8: [[u8; 8]; 95] = [
+    [0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00], // space
+    [0x00, 0x00, 0x06, 0x5F, 0x5F, 0x06, 0x00, 0x00], // !
+    [0x00, 0x03, 0x03, 0x00, 0x03, 0x03, 0x00, 0x00], // "
+    [0x14, 0x7F, 0x7F, 0x14, 0x7F, 0x7F, 0x14, 0x00], // #
+    [0x24, 0x2E, 0x6B, 0x6B, 0x3A, 0x12, 0x00, 0x00], // $
+    [0x46, 0x66, 0x30, 0x18, 0x0C, 0x66, 0x62, 0x00], // %
+    [0x30, 0x7A, 0x4F, 0x5D, 0x37, 0x7A, 0x48, 0x00], // &
+    [0x04, 0x07, 0x03, 0x00, 0x00, 0x00, 0x00, 0x00], // '
+    [0x00, 0x1C, 0x3E, 0x63, 0x41, 0x00, 0x00, 0x00], // (
+    [0x00, 0x41, 0x63, 0x3E, 0x1C, 0x00, 0x00, 0x00], // )
+    [0x08, 0x2A, 0x3E, 0x1C, 0x1C, 0x3E, 0x2A, 0x08], // *
+    [0x08, 0x08, 0x3E, 0x3E, 0x08, 0x08, 0x00, 0x00], // +
+    [0x00, 0x80, 0xE0, 0x60, 0x00, 0x00, 0x00, 0x00], // ,
+    [0x08, 0x08, 0x08, 0x08, 0x08, 0x08, 0x00, 0x00], // -
+    [0x00, 0x00, 0x60, 0x60, 0x00, 0x00, 0x00, 0x00], // .
+    [0x60, 0x30, 0x18, 0x0C, 0x06, 0x03, 0x01, 0x00], // /
+    [0x3E, 0x7F, 0x71, 0x59, 0x4D, 0x7F, 0x3E, 0x00], // 0
+    [0x40, 0x42, 0x7F, 0x7F, 0x40, 0x40, 0x00, 0x00], // 1
+    [0x62, 0x73, 0x59, 0x49, 0x6F, 0x66, 0x00, 0x00], // 2
+    [0x22, 0x63, 0x49, 0x49, 0x7F, 0x36, 0x00, 0x00], // 3
+    [0x18, 0x1C, 0x16, 0x53, 0x7F, 0x7F, 0x50, 0x00], // 4
+    [0x27, 0x67, 0x45, 0x45, 0x7D, 0x39, 0x00, 0x00], // 5
+    [0x3C, 0x7E, 0x4B, 0x49, 0x79, 0x30, 0x00, 0x00], // 6
+    [0x03, 0x03, 0x71, 0x79, 0x0F, 0x07, 0x00, 0x00], // 7
+    [0x36, 0x7F, 0x49, 0x49, 0x7F, 0x36, 0x00, 0x00], // 8
+    [0x06, 0x4F, 0x49, 0x69, 0x3F, 0x1E, 0x00, 0x00], // 9
+    [0x00, 0x00, 0x66, 0x66, 0x00, 0x00, 0x00, 0x00], // :
+    [0x00, 0x80, 0xE6, 0x66, 0x00, 0x00, 0x00, 0x00], // ;
+    [0x08, 0x1C, 0x36, 0x63, 0x41, 0x00, 0x00, 0x00], // <
+    [0x24, 0x24, 0x24, 0x24, 0x24, 0x24, 0x00, 0x00], // =
+    [0x00, 0x41, 0x63, 0x36, 0x1C, 0x08, 0x00, 0x00], // >
+    [0x02, 0x03, 0x51, 0x59, 0x0F, 0x06, 0x00, 0x00], // ?
+    [0x3E, 0x7F, 0x41, 0x5D, 0x5D, 0x1F, 0x1E, 0x00], // @
+    [0x7C, 0x7E, 0x13, 0x13, 0x7E, 0x7C, 0x00, 0x00], // A
+    [0x41, 0x7F, 0x7F, 0x49, 0x49, 0x7F, 0x36, 0x00], // B
+    [0x1C, 0x3E, 0x63, 0x41, 0x41, 0x63, 0x22, 0x00], // C
+    [0x41, 0x7F, 0x7F, 0x41, 0x63, 0x3E, 0x1C, 0x00], // D
+    [0x41, 0x7F, 0x7F, 0x49, 0x5D, 0x41, 0x63, 0x00], // E
+    [0x41, 0x7F, 0x7F, 0x49, 0x1D, 0x01, 0x03, 0x00], // F
+    [0x1C, 0x3E, 0x63, 0x41, 0x51, 0x73, 0x72, 0x00], // G
+    [0x7F, 0x7F, 0x08, 0x08, 0x7F, 0x7F, 0x00, 0x00], // H
+    [0x00, 0x41, 0x7F, 0x7F, 0x41, 0x00, 0x00, 0x00], // I
+    [0x30, 0x70, 0x40, 0x41, 0x7F, 0x3F, 0x01, 0x00], // J
+    [0x41, 0x7F, 0x7F, 0x08, 0x1C, 0x77, 0x63, 0x00], // K
+    [0x41, 0x7F, 0x7F, 0x41, 0x40, 0x60, 0x70, 0x00], // L
+    [0x7F, 0x7F, 0x0E, 0x1C, 0x0E, 0x7F, 0x7F, 0x00], // M
+    [0x7F, 0x7F, 0x06, 0x0C, 0x18, 0x7F, 0x7F, 0x00], // N
+    [0x1C, 0x3E, 0x63, 0x41, 0x63, 0x3E, 0x1C, 0x00], // O
+    [0x41, 0x7F, 0x7F, 0x49, 0x09, 0x0F, 0x06, 0x00], // P
+    [0x1E, 0x3F, 0x21, 0x71, 0x7F, 0x5E, 0x00, 0x00], // Q
+    [0x41, 0x7F, 0x7F, 0x09, 0x19, 0x7F, 0x66, 0x00], // R
+    [0x26, 0x6F, 0x4D, 0x59, 0x73, 0x32, 0x00, 0x00], // S
+    [0x03, 0x41, 0x7F, 0x7F, 0x41, 0x03, 0x00, 0x00], // T
+    [0x7F, 0x7F, 0x40, 0x40, 0x7F, 0x7F, 0x00, 0x00], // U
+    [0x1F, 0x3F, 0x60, 0x60, 0x3F, 0x1F, 0x00, 0x00], // V
+    [0x7F, 0x7F, 0x30, 0x18, 0x30, 0x7F, 0x7F, 0x00], // W
+    [0x43, 0x67, 0x3C, 0x18, 0x3C, 0x67, 0x43, 0x00], // X
+    [0x07, 0x4F, 0x78, 0x78, 0x4F, 0x07, 0x00, 0x00], // Y
+    [0x47, 0x63, 0x71, 0x59, 0x4D, 0x67, 0x73, 0x00], // Z
+    [0x00, 0x7F, 0x7F, 0x41, 0x41, 0x00, 0x00, 0x00], // [
+    [0x01, 0x03, 0x06, 0x0C, 0x18, 0x30, 0x60, 0x00], // \
+    [0x00, 0x41, 0x41, 0x7F, 0x7F, 0x00, 0x00, 0x00], // ]
+    [0x08, 0x0C, 0x06, 0x03, 0x06, 0x0C, 0x08, 0x00], // ^
+    [0x80, 0x80, 0x80, 0x80, 0x80, 0x80, 0x80, 0x80], // _
+    [0x00, 0x00, 0x03, 0x07, 0x04, 0x00, 0x00, 0x00], // `
+    [0x20, 0x74, 0x54, 0x54, 0x3C, 0x78, 0x40, 0x00], // a
+    [0x41, 0x7F, 0x3F, 0x48, 0x48, 0x78, 0x30, 0x00], // b
+    [0x38, 0x7C, 0x44, 0x44, 0x6C, 0x28, 0x00, 0x00], // c
+    [0x30, 0x78, 0x48, 0x49, 0x3F, 0x7F, 0x40, 0x00], // d
+    [0x38, 0x7C, 0x54, 0x54, 0x5C, 0x18, 0x00, 0x00], // e
+    [0x48, 0x7E, 0x7F, 0x49, 0x03, 0x02, 0x00, 0x00], // f
+    [0x98, 0xBC, 0xA4, 0xA4, 0xF8, 0x7C, 0x04, 0x00], // g
+    [0x41, 0x7F, 0x7F, 0x08, 0x04, 0x7C, 0x78, 0x00], // h
+    [0x00, 0x44, 0x7D, 0x7D, 0x40, 0x00, 0x00, 0x00], // i
+    [0x60, 0xE0, 0x80, 0x80, 0xFD, 0x7D, 0x00, 0x00], // j
+    [0x41, 0x7F, 0x7F, 0x10, 0x38, 0x6C, 0x44, 0x00], // k
+    [0x00, 0x41, 0x7F, 0x7F, 0x40, 0x00, 0x00, 0x00], // l
+    [0x7C, 0x7C, 0x18, 0x38, 0x1C, 0x7C, 0x78, 0x00], // m
+    [0x7C, 0x7C, 0x04, 0x04, 0x7C, 0x78, 0x00, 0x00], // n
+    [0x38, 0x7C, 0x44, 0x44, 0x7C, 0x38, 0x00, 0x00], // o
+    [0x84, 0xFC, 0xF8, 0xA4, 0x24, 0x3C, 0x18, 0x00], // p
+    [0x18, 0x3C, 0x24, 0xA4, 0xF8, 0xFC, 0x84, 0x00], // q
+    [0x44, 0x7C, 0x78, 0x4C, 0x04, 0x1C, 0x18, 0x00], // r
+    [0x48, 0x5C, 0x54, 0x54, 0x74, 0x24, 0x00, 0x00], // s
+    [0x00, 0x04, 0x3E, 0x7F, 0x44, 0x24, 0x00, 0x00], // t
+    [0x3C, 0x7C, 0x40, 0x40, 0x3C, 0x7C, 0x40, 0x00], // u
+    [0x1C, 0x3C, 0x60, 0x60, 0x3C, 0x1C, 0x00, 0x00], // v
+    [0x3C, 0x7C, 0x70, 0x38, 0x70, 0x7C, 0x3C, 0x00], // w
+    [0x44, 0x6C, 0x38, 0x10, 0x38, 0x6C, 0x44, 0x00], // x
+    [0x9C, 0xBC, 0xA0, 0xA0, 0xFC, 0x7C, 0x00, 0x00], // y
+    [0x4C, 0x64, 0x74, 0x5C, 0x4C, 0x64, 0x00, 0x00], // z
+    [0x08, 0x08, 0x3E, 0x77, 0x41, 0x41, 0x00, 0x00], // {
+    [0x00, 0x00, 0x00, 0x77, 0x77, 0x00, 0x00, 0x00], // |
+    [0x41, 0x41, 0x77, 0x3E, 0x08, 0x08, 0x00, 0x00], // }
+    [0x02, 0x03, 0x01, 0x03, 0x02, 0x03, 0x01, 0x00], // ~
+];
+
+#[cfg(test)]
+mod tests {
+    use core::fmt::Write;
+
+    #[cfg(feature = "async")]
+    use display_interface::AsyncWriteOnlyDataCommand as WriteOnlyDataCommand;
+    #[cfg(not(feature = "async"))]
+    use display_interface::WriteOnlyDataCommand;
+    #[cfg(feature = "async")]
+    use embedded_hal_async::delay::DelayNs;
+    #[cfg(not(feature = "async"))]
+    use hal::delay::DelayNs;
+
+    extern crate std;
+
+    use std::vec;
+
+    use super::{TerminalMode, FONT_8X8};
+    use crate::{
+        displayrotation::DisplayRotation,
+        displays::{DisplayVariant, InitConfig, MonochromeVariant},
+        mode::displaymode::DisplayModeTrait,
+        properties::DisplayProperties,
+        test_helpers::{block_on, RecordingInterface, Transfer},
+        Error,
+    };
+
+    /// Panel of three characters by two lines
+    struct Small;
+
+    impl DisplayVariant for Small {
+        const WIDTH: u16 = 24;
+        const HEIGHT: u16 = 16;
+        type Buffer = [u8; 24 * 16 / 8];
+    }
+
+    #[maybe_async_cfg::maybe(
+        sync(cfg(not(feature = "async")),),
+        async(feature = "async"),
+        keep_self
+    )]
+    impl MonochromeVariant for Small {
+        async fn init_column_mode<DI, D>(
+            _iface: &mut DI,
+            _config: &InitConfig,
+            _delay: &mut D,
+        ) -> Result<(), Error>
+        where
+            DI: WriteOnlyDataCommand,
+            D: DelayNs,
+        {
+            Ok(())
+        }
+    }
+
+    fn terminal() -> TerminalMode<Small, RecordingInterface> {
+        let mut terminal = TerminalMode::new(DisplayProperties::new(
+            Small,
+            RecordingInterface::new(),
+            DisplayRotation::Rotate0,
+        ));
+        terminal.dirty = [0; super::MAX_CELLS];
+        terminal
+    }
+
+    fn text(terminal: &TerminalMode<Small, RecordingInterface>) -> &[u8] {
+        &terminal.cells[..6]
+    }
+
+    #[test]
+    fn wraps_at_end_of_line() {
+        let mut terminal = terminal();
+        terminal.print("abc");
+        assert_eq!(terminal.get_position(), (3, 0));
+
+        terminal.print("d");
+        assert_eq!(text(&terminal), b"abcd  ");
+        assert_eq!(terminal.get_position(), (1, 1));
+    }
+
+    #[test]
+    fn new_line_after_full_line_does_not_skip_a_line() {
+        let mut terminal = terminal();
+        terminal.print("abc\nd");
+        assert_eq!(text(&terminal), b"abcd  ");
+    }
+
+    #[test]
+    fn scrolls_up_on_overflow() {
+        let mut terminal = terminal();
+        terminal.print("ab\ncd\nef");
+        assert_eq!(text(&terminal), b"cd ef ");
+        assert_eq!(terminal.get_position(), (2, 1));
+    }
+
+    #[test]
+    fn marks_only_changed_cells() {
+        let mut terminal = terminal();
+        terminal.print("a b");
+        assert_eq!(terminal.dirty[..2], [0b101, 0]);
+
+        // Scrolling moves "a b" up and blanks the second line, which is
+        // already blank
+        terminal.dirty = [0; super::MAX_CELLS];
+        terminal.print("\n\n");
+        assert_eq!(terminal.dirty[..2], [0b101, 0]);
+    }
+
+    #[test]
+    fn replaces_unprintable_characters() {
+        let mut terminal = terminal();
+        terminal.print("é\t");
+        assert_eq!(text(&terminal), b"??    ");
+    }
+
+    #[test]
+    fn formats_text() {
+        let mut terminal = terminal();
+        write!(terminal, "{}\r{}", 123, 4).unwrap();
+        assert_eq!(text(&terminal), b"423   ");
+    }
+
+    #[test]
+    fn rejects_positions_outside_the_grid() {
+        let mut terminal = terminal();
+        assert!(matches!(
+            terminal.set_position(3, 0),
+            Err(Error::OutOfBounds)
+        ));
+        terminal.set_position(2, 1).unwrap();
+        terminal.print("x");
+        assert_eq!(text(&terminal), b"     x");
+    }
+
+    #[test]
+    fn flush_sends_only_touched_cells() {
+        let mut terminal = terminal();
+        terminal.print("a b");
+        block_on(terminal.flush()).unwrap();
+
+        let iface = terminal.release().release();
+        assert_eq!(
+            iface.transfers,
+            [
+                Transfer::Commands(vec![0xB0, 0x00, 0x10]),
+                Transfer::Data(FONT_8X8[usize::from(b'a' - b' ')].to_vec()),
+                Transfer::Commands(vec![0xB0, 0x00, 0x11]),
+                Transfer::Data(FONT_8X8[usize::from(b'b' - b' ')].to_vec()),
+            ]
+        );
+    }
+}
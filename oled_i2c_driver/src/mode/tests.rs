@@ -0,0 +1,160 @@
+//! Golden image tests: the modes draw into an emulated controller and the
+//! image on its glass is compared against the PBM files in `tests/golden`
+
+use core::fmt::Write;
+
+use super::{displaymode::DisplayModeTrait, GraphicsMode, TerminalMode};
+use crate::{
+    displayrotation::DisplayRotation,
+    displays::{
+        sh1106::Sh1106,
+        sh1107::Sh1107,
+        sh1108::Sh1108,
+        ssd1309::Ssd1309,
+        MonochromeVariant,
+        Screen128x128,
+        Screen128x64,
+        Screen64x128,
+        Screen64x160,
+    },
+    test_helpers::{assert_golden, block_on, EmulatedController, Image},
+    Builder,
+};
+
+/// Connect `builder` to an emulated controller and initialise the display
+fn graphics<DV: MonochromeVariant>(
+    builder: Builder<DV>,
+) -> GraphicsMode<DV, EmulatedController> {
+    let mut display: GraphicsMode<_, _> =
+        builder.connect(EmulatedController::new::<DV>()).into();
+    block_on(display.init()).unwrap();
+    display
+}
+
+/// Flush and return the image on the glass
+fn visible<DV: MonochromeVariant>(
+    mut display: GraphicsMode<DV, EmulatedController>,
+) -> Image {
+    block_on(display.flush()).unwrap();
+    display.release().release().visible()
+}
+
+/// Light `(x, y)` for each pixel of a frame around the display and an "F" in
+/// its top left corner, which looks different under every rotation and mirror
+fn marker(width: u32, height: u32) -> impl Iterator<Item = (u32, u32)> {
+    let frame = (0..width)
+        .flat_map(move |x| [(x, 0), (x, height - 1)])
+        .chain((0..height).flat_map(move |y| [(0, y), (width - 1, y)]));
+    let stem = (4..20).flat_map(|y| (4..7).map(move |x| (x, y)));
+    let top = (4..7).flat_map(|y| (7..16).map(move |x| (x, y)));
+    let middle = (11..13).flat_map(|y| (7..13).map(move |x| (x, y)));
+
+    frame.chain(stem).chain(top).chain(middle)
+}
+
+fn draw_marker<DV: MonochromeVariant>(
+    display: &mut GraphicsMode<DV, EmulatedController>,
+) {
+    let (width, height) = display.get_dimensions();
+    for (x, y) in marker(width.into(), height.into()) {
+        display.set_pixel(x, y, 1);
+    }
+}
+
+#[test]
+fn drawing_appears_as_drawn() {
+    let mut display = graphics(Builder::new(Sh1106::<Screen128x64>::new()));
+    draw_marker(&mut display);
+    let image = visible(display);
+
+    let mut expected = Image::new(128, 64);
+    for (x, y) in marker(128, 64) {
+        expected.set(x as usize, y as usize, true);
+    }
+    assert_eq!(image, expected);
+    assert!(image.get(5, 5) && !image.get(5, 25));
+}
+
+#[test]
+fn sh1106_rotate0() {
+    let mut display = graphics(Builder::new(Sh1106::<Screen128x64>::new()));
+    draw_marker(&mut display);
+    assert_golden("sh1106_rotate0", &visible(display));
+}
+
+#[test]
+fn sh1106_rotate180() {
+    let mut display = graphics(
+        Builder::new(Sh1106::<Screen128x64>::new())
+            .with_rotation(DisplayRotation::Rotate180),
+    );
+    draw_marker(&mut display);
+    assert_golden("sh1106_rotate180", &visible(display));
+}
+
+#[test]
+fn sh1107_rotate90() {
+    let mut display = graphics(
+        Builder::new(Sh1107::<Screen64x128>::new())
+            .with_rotation(DisplayRotation::Rotate90),
+    );
+    draw_marker(&mut display);
+    assert_golden("sh1107_64x128_rotate90", &visible(display));
+}
+
+#[test]
+fn sh1108_mirrored() {
+    let mut display = graphics(
+        Builder::new(Sh1108::<Screen64x160>::new())
+            .with_mirror_horizontal(true),
+    );
+    draw_marker(&mut display);
+    assert_golden("sh1108_64x160_mirrored", &visible(display));
+}
+
+#[test]
+fn ssd1309_scrolled() {
+    let mut display = graphics(Builder::new(Ssd1309::<Screen128x64>::new()));
+    draw_marker(&mut display);
+    block_on(display.set_scroll_position(8)).unwrap();
+    assert_golden("ssd1309_scrolled", &visible(display));
+}
+
+#[test]
+fn sh1107_scrolls_through_all_128_rows() {
+    let mut display = graphics(Builder::new(Sh1107::<Screen128x128>::new()));
+    draw_marker(&mut display);
+    block_on(display.set_scroll_position(100)).unwrap();
+    let image = visible(display);
+
+    // Row 100 is at the top, the rows above it follow at the bottom
+    let mut expected = Image::new(128, 128);
+    for (x, y) in marker(128, 128) {
+        expected.set(x as usize, (y as usize + 128 - 100) % 128, true);
+    }
+    assert_eq!(image, expected);
+}
+
+#[test]
+fn ssd1309_display_offset() {
+    let mut display = graphics(
+        Builder::new(Ssd1309::<Screen128x64>::new())
+            .with_rotation(DisplayRotation::Rotate180),
+    );
+    draw_marker(&mut display);
+    block_on(display.set_display_offset(8)).unwrap();
+    assert_golden("ssd1309_offset_rotate180", &visible(display));
+}
+
+#[test]
+fn terminal_text() {
+    let mut display: TerminalMode<_, _> =
+        Builder::new(Sh1106::<Screen128x64>::new())
+            .connect(EmulatedController::new::<Sh1106<Screen128x64>>())
+            .into();
+    block_on(display.init()).unwrap();
+    write!(display, "Hello, world!\nLine two").unwrap();
+    block_on(display.flush()).unwrap();
+
+    assert_golden("sh1106_terminal", &display.release().release().visible());
+}
@@ -1 +1,4 @@
-pub use super::{displayrotation::DisplayRotation, mode::GraphicsMode};
+pub use super::{
+    displayrotation::DisplayRotation,
+    mode::{GraphicsMode, GrayscaleMode, TerminalMode},
+};
@@ -1,25 +1,55 @@
-use display_interface::{AsyncWriteOnlyDataCommand, DataFormat, DisplayError};
+#[cfg(feature = "async")]
+use display_interface::AsyncWriteOnlyDataCommand as WriteOnlyDataCommand;
+use display_interface::DataFormat;
+#[cfg(not(feature = "async"))]
+use display_interface::WriteOnlyDataCommand;
+#[cfg(feature = "async")]
+use embedded_hal_async::delay::DelayNs;
+#[cfg(not(feature = "async"))]
+use hal::delay::DelayNs;
+use hal::digital::{Error as _, OutputPin};
 
 use crate::{
-    command::Command,
+    builder::{NoDelay, NoOutputPin, PowerDelay},
+    command::{Command, CommandBatch, NFrames, ScrollDirection},
     displayrotation::DisplayRotation,
-    displays::DisplayVariant,
+    displays::{
+        DisplayVariant,
+        GrayscaleVariant,
+        InitConfig,
+        MonochromeVariant,
+    },
+    Error,
 };
 
+/// Time for VCC to settle after switching it on or off
+const VCC_SETTLE_MS: u32 = 100;
+
 /// Display properties struct
-pub struct DisplayProperties<DV, DI> {
+pub struct DisplayProperties<
+    DV,
+    DI,
+    RST = NoOutputPin,
+    VCC = NoOutputPin,
+    D = NoDelay,
+> {
     _variant: DV,
     iface: DI,
+    init_config: InitConfig,
     display_rotation: DisplayRotation,
+    mirror_horizontal: bool,
+    mirror_vertical: bool,
     draw_area_start: (u8, u8),
     draw_area_end: (u8, u8),
     draw_column: u8,
     draw_row: u8,
+    reset_pin: Option<RST>,
+    vcc_pin: Option<VCC>,
+    delay: D,
 }
 
 impl<DV, DI> DisplayProperties<DV, DI>
 where
-    DI: AsyncWriteOnlyDataCommand,
     DV: DisplayVariant,
 {
     /// Create new DisplayProperties instance
@@ -31,34 +61,227 @@ where
         DisplayProperties {
             _variant: variant,
             iface,
+            init_config: DV::INIT_CONFIG,
             display_rotation,
+            mirror_horizontal: false,
+            mirror_vertical: false,
             draw_area_start: (0, 0),
             draw_area_end: (0, 0),
             draw_column: 0,
             draw_row: 0,
+            reset_pin: None,
+            vcc_pin: None,
+            delay: NoDelay,
+        }
+    }
+}
+
+#[maybe_async_cfg::maybe(
+    sync(cfg(not(feature = "async")),),
+    async(feature = "async"),
+    keep_self
+)]
+impl<DV, DI, RST, VCC, D> DisplayProperties<DV, DI, RST, VCC, D>
+where
+    DI: WriteOnlyDataCommand,
+    DV: DisplayVariant,
+{
+    /// Use the given reset and VCC enable pins and delay for the power-up and
+    /// power-down sequences
+    pub fn with_power<R, V, E>(
+        self,
+        reset_pin: Option<R>,
+        vcc_pin: Option<V>,
+        delay: E,
+    ) -> DisplayProperties<DV, DI, R, V, E> {
+        DisplayProperties {
+            _variant: self._variant,
+            iface: self.iface,
+            init_config: self.init_config,
+            display_rotation: self.display_rotation,
+            mirror_horizontal: self.mirror_horizontal,
+            mirror_vertical: self.mirror_vertical,
+            draw_area_start: self.draw_area_start,
+            draw_area_end: self.draw_area_end,
+            draw_column: self.draw_column,
+            draw_row: self.draw_row,
+            reset_pin,
+            vcc_pin,
+            delay,
+        }
+    }
+
+    /// Replace the variant's default initialisation parameters. Takes effect on
+    /// the next initialisation.
+    pub fn with_init_config(self, init_config: InitConfig) -> Self {
+        DisplayProperties {
+            init_config,
+            ..self
+        }
+    }
+
+    /// Mirror the image horizontally and/or vertically, as seen after
+    /// rotation. Takes effect on the next `init_column_mode` or
+    /// `set_rotation`.
+    pub fn with_mirror(self, horizontal: bool, vertical: bool) -> Self {
+        DisplayProperties {
+            mirror_horizontal: horizontal,
+            mirror_vertical: vertical,
+            ..self
+        }
+    }
+
+    /// Pulse the reset pin, if there is one
+    async fn reset(&mut self) -> Result<(), Error>
+    where
+        RST: OutputPin,
+        D: PowerDelay<RST, VCC>,
+    {
+        let Some(reset) = self.reset_pin.as_mut() else {
+            return Ok(());
+        };
+
+        reset.set_high().map_err(|e| Error::Pin(e.kind()))?;
+        self.delay.delay().delay_ms(1).await;
+        reset.set_low().map_err(|e| Error::Pin(e.kind()))?;
+        self.delay.delay().delay_ms(10).await;
+        reset.set_high().map_err(|e| Error::Pin(e.kind()))?;
+        self.delay.delay().delay_ms(1).await;
+
+        Ok(())
+    }
+
+    /// Switch on VCC, if there is a pin for it, then the display once VCC has
+    /// settled
+    async fn enable_vcc(&mut self) -> Result<(), Error>
+    where
+        VCC: OutputPin,
+        D: PowerDelay<RST, VCC>,
+    {
+        if let Some(vcc) = self.vcc_pin.as_mut() {
+            vcc.set_high().map_err(|e| Error::Pin(e.kind()))?;
+            self.delay.delay().delay_ms(VCC_SETTLE_MS).await;
+        }
+
+        self.display_on(true).await
+    }
+
+    /// Run the power-down sequence: display off, then VCC off and wait for it
+    /// to discharge. VDD can be removed once this returns.
+    pub async fn power_down(&mut self) -> Result<(), Error>
+    where
+        VCC: OutputPin,
+        D: PowerDelay<RST, VCC>,
+    {
+        Command::DisplayOn(false).send(&mut self.iface).await?;
+
+        if let Some(vcc) = self.vcc_pin.as_mut() {
+            vcc.set_low().map_err(|e| Error::Pin(e.kind()))?;
+            self.delay.delay().delay_ms(VCC_SETTLE_MS).await;
+        }
+
+        Ok(())
+    }
+
+    // Get the configured display size
+    //pub fn get_size(&self) -> DisplaySize {
+    //    self.display_size
+    //}
+
+    /// Release the display interface, e.g. to hand the bus to another driver.
+    /// The pins and delay are dropped.
+    pub fn release(self) -> DI {
+        self.iface
+    }
+
+    /// Get display dimensions, taking into account the current rotation of the
+    /// display
+    pub fn get_dimensions(&self) -> (u16, u16) {
+        let (w, h) = DV::dimensions();
+
+        match self.display_rotation {
+            DisplayRotation::Rotate0 | DisplayRotation::Rotate180 => (w, h),
+            DisplayRotation::Rotate90 | DisplayRotation::Rotate270 => (h, w),
         }
     }
 
+    /// Get the display rotation
+    pub fn get_rotation(&self) -> DisplayRotation {
+        self.display_rotation
+    }
+
+    /// Get whether the image is mirrored horizontally and vertically
+    pub fn get_mirror(&self) -> (bool, bool) {
+        (self.mirror_horizontal, self.mirror_vertical)
+    }
+
+    /// Turn the display on or off. The display can be drawn to and retains all
+    /// of its memory even while off.
+    pub async fn display_on(&mut self, on: bool) -> Result<(), Error> {
+        Command::DisplayOn(on).send(&mut self.iface).await?;
+
+        Ok(())
+    }
+}
+
+#[maybe_async_cfg::maybe(
+    sync(cfg(not(feature = "async")),),
+    async(feature = "async"),
+    keep_self
+)]
+impl<DV, DI, RST, VCC, D> DisplayProperties<DV, DI, RST, VCC, D>
+where
+    DI: WriteOnlyDataCommand,
+    DV: MonochromeVariant,
+{
     /// Initialise the display in column mode (i.e. a byte walks down a column
     /// of 8 pixels) with column 0 on the left and column _(display_width -
     /// 1)_ on the right.
-    pub async fn init_column_mode(&mut self) -> Result<(), DisplayError> {
+    ///
+    /// This runs the whole power-up sequence, assuming VDD is already on:
+    /// reset pulse, the variant's init commands, then VCC and display on.
+    pub async fn init_column_mode(&mut self) -> Result<(), Error>
+    where
+        RST: OutputPin,
+        VCC: OutputPin,
+        D: PowerDelay<RST, VCC>,
+    {
+        self.reset().await?;
+
         let display_rotation = self.display_rotation;
-        DV::init_column_mode(&mut self.iface).await?;
+        DV::init_column_mode(
+            &mut self.iface,
+            &self.init_config,
+            &mut self.delay.delay(),
+        )
+        .await?;
         self.set_rotation(display_rotation).await?;
 
-        Ok(())
+        self.enable_vcc().await
     }
 
     /// Set the position in the framebuffer of the display where any sent data
     /// should be drawn. This method can be used for changing the affected
     /// area on the screen as well as (re-)setting the start point of the
     /// next `draw` call.
+    ///
+    /// Returns [`Error::OutOfBounds`] if the area is empty or does not fit on
+    /// the panel.
     pub async fn set_draw_area(
         &mut self,
         start: (u8, u8),
         end: (u8, u8),
-    ) -> Result<(), DisplayError> {
+    ) -> Result<(), Error> {
+        let column_end = u16::from(DV::COLUMN_OFFSET) + DV::WIDTH;
+        if start.0 < DV::COLUMN_OFFSET
+            || start.0 >= end.0
+            || start.1 >= end.1
+            || u16::from(end.0) > column_end
+            || u16::from(end.1) > DV::HEIGHT / 8
+        {
+            return Err(Error::OutOfBounds);
+        }
+
         self.draw_area_start = start;
         self.draw_area_end = end;
         self.draw_column = start.0;
@@ -70,17 +293,10 @@ where
     /// Send the data to the display for drawing at the current position in the
     /// framebuffer and advance the position accordingly. Cf.
     /// `set_draw_area` to modify the affected area by this method.
-    pub async fn draw(
-        &mut self,
-        mut buffer: &[u8],
-    ) -> Result<(), DisplayError> {
+    pub async fn draw(&mut self, mut buffer: &[u8]) -> Result<(), Error> {
         while !buffer.is_empty() {
-            let count = self.draw_area_end.0 - self.draw_column;
-            self.iface
-                .send_data(DataFormat::U8(&buffer[..count as usize]))
-                .await?;
-            self.draw_column += count;
-
+            // Move on to the next row only once there is data for it, so that
+            // filling the area exactly sends no address
             if self.draw_column >= self.draw_area_end.0 {
                 self.draw_column = self.draw_area_start.0;
 
@@ -92,89 +308,328 @@ where
                 self.send_draw_address().await?;
             }
 
-            buffer = &buffer[count as usize..];
+            let count = usize::from(self.draw_area_end.0 - self.draw_column)
+                .min(buffer.len());
+            self.iface
+                .send_data(DataFormat::U8(&buffer[..count]))
+                .await?;
+            self.draw_column += count as u8;
+
+            buffer = &buffer[count..];
         }
 
         Ok(())
     }
 
-    async fn send_draw_address(&mut self) -> Result<(), DisplayError> {
-        if DV::LARGE_PAGE_ADDRESS {
+    async fn send_draw_address(&mut self) -> Result<(), Error> {
+        let page = if DV::LARGE_PAGE_ADDRESS {
             Command::LargePageAddress(self.draw_row)
-                .send(&mut self.iface)
-                .await?;
         } else {
             Command::PageAddress(self.draw_row)
-                .send(&mut self.iface)
-                .await?;
-        }
-        Command::ColumnAddressLow(0xF & self.draw_column)
+        };
+        CommandBatch::new()
+            .push(page)
+            .push(Command::ColumnAddressLow(0xF & self.draw_column))
+            .push(Command::ColumnAddressHigh(0xF & (self.draw_column >> 4)))
             .send(&mut self.iface)
             .await?;
-        Command::ColumnAddressHigh(0xF & (self.draw_column >> 4))
-            .send(&mut self.iface)
-            .await
-    }
 
-    // Get the configured display size
-    //pub fn get_size(&self) -> DisplaySize {
-    //    self.display_size
-    //}
-
-    /// Get display dimensions, taking into account the current rotation of the
-    /// display
-    pub fn get_dimensions(&self) -> (u8, u8) {
-        let (w, h) = DV::dimensions();
-
-        match self.display_rotation {
-            DisplayRotation::Rotate0 | DisplayRotation::Rotate180 => (w, h),
-            DisplayRotation::Rotate90 | DisplayRotation::Rotate270 => (h, w),
-        }
-    }
-
-    /// Get the display rotation
-    pub fn get_rotation(&self) -> DisplayRotation {
-        self.display_rotation
+        Ok(())
     }
 
     /// Set the display rotation
     pub async fn set_rotation(
         &mut self,
         display_rotation: DisplayRotation,
-    ) -> Result<(), DisplayError> {
+    ) -> Result<(), Error> {
         self.display_rotation = display_rotation;
 
-        match display_rotation {
-            DisplayRotation::Rotate0 => {
-                Command::SegmentRemap(true).send(&mut self.iface).await?;
-                Command::ReverseComDir(true).send(&mut self.iface).await
-            }
-            DisplayRotation::Rotate90 => {
-                Command::SegmentRemap(false).send(&mut self.iface).await?;
-                Command::ReverseComDir(true).send(&mut self.iface).await
+        // Quarter turns transpose the image, which the controller cannot do.
+        // The modes apply them in software on top of the unflipped panel.
+        let (segment_remap, reverse_com_dir) = match display_rotation {
+            DisplayRotation::Rotate0
+            | DisplayRotation::Rotate90
+            | DisplayRotation::Rotate270 => (true, true),
+            DisplayRotation::Rotate180 => (false, false),
+        };
+        // After a quarter turn, display columns run along the panel's rows
+        let (flip_columns, flip_rows) = match display_rotation {
+            DisplayRotation::Rotate0 | DisplayRotation::Rotate180 => {
+                (self.mirror_horizontal, self.mirror_vertical)
             }
-            DisplayRotation::Rotate180 => {
-                Command::SegmentRemap(false).send(&mut self.iface).await?;
-                Command::ReverseComDir(false).send(&mut self.iface).await
+            DisplayRotation::Rotate90 | DisplayRotation::Rotate270 => {
+                (self.mirror_vertical, self.mirror_horizontal)
             }
-            DisplayRotation::Rotate270 => {
-                Command::SegmentRemap(true).send(&mut self.iface).await?;
-                Command::ReverseComDir(false).send(&mut self.iface).await
+        };
+
+        CommandBatch::new()
+            .push(Command::SegmentRemap(segment_remap ^ flip_columns))
+            .push(Command::ReverseComDir(reverse_com_dir ^ flip_rows))
+            .send(&mut self.iface)
+            .await?;
+
+        Ok(())
+    }
+
+    /// Mirror the image horizontally and/or vertically, as seen after
+    /// rotation. The controller flips the RAM mapping, so data already on the
+    /// display has to be sent again.
+    pub async fn set_mirror(
+        &mut self,
+        horizontal: bool,
+        vertical: bool,
+    ) -> Result<(), Error> {
+        self.mirror_horizontal = horizontal;
+        self.mirror_vertical = vertical;
+
+        self.set_rotation(self.display_rotation).await
+    }
+
+    /// Set the display contrast
+    pub async fn set_contrast(&mut self, contrast: u8) -> Result<(), Error> {
+        Command::Contrast(contrast).send(&mut self.iface).await?;
+
+        Ok(())
+    }
+
+    /// Set the RAM row shown on the first row of the panel. Rows wrap around
+    /// at the end of the display RAM. Returns [`Error::OutOfBounds`] if `line`
+    /// is not below the variant's
+    /// [`START_LINES`](MonochromeVariant::START_LINES).
+    pub async fn set_start_line(&mut self, line: u8) -> Result<(), Error> {
+        if u16::from(line) >= DV::START_LINES {
+            return Err(Error::OutOfBounds);
+        }
+
+        let command = if DV::START_LINES > 64 {
+            Command::LargeStartLine(line)
+        } else {
+            Command::StartLine(line)
+        };
+        command.send(&mut self.iface).await?;
+
+        Ok(())
+    }
+
+    /// Shift the mapping of the panel rows to the controller outputs by
+    /// `offset` rows. Returns [`Error::OutOfBounds`] if `offset` is not below
+    /// the controller's number of outputs,
+    /// [`START_LINES`](MonochromeVariant::START_LINES).
+    pub async fn set_display_offset(
+        &mut self,
+        offset: u8,
+    ) -> Result<(), Error> {
+        if u16::from(offset) >= DV::START_LINES {
+            return Err(Error::OutOfBounds);
+        }
+
+        Command::DisplayOffset(offset).send(&mut self.iface).await?;
+
+        Ok(())
+    }
+
+    /// Scroll the image up by `position` rows through the start line, without
+    /// touching the display RAM. The position wraps around at the variant's
+    /// [`START_LINES`](MonochromeVariant::START_LINES), so rows scrolled off
+    /// the top come back in at the bottom once the RAM rows below the panel
+    /// have passed. After a quarter turn the image moves left instead, as the
+    /// panel rows then run across the display. Mirroring is taken into
+    /// account.
+    pub async fn set_scroll_position(
+        &mut self,
+        position: u16,
+    ) -> Result<(), Error> {
+        let lines = DV::START_LINES;
+        // Whether the panel rows run against the display axis the image moves
+        // along
+        let reversed = match self.display_rotation {
+            DisplayRotation::Rotate0 | DisplayRotation::Rotate180 => {
+                self.mirror_vertical
             }
+            DisplayRotation::Rotate90 => self.mirror_horizontal,
+            DisplayRotation::Rotate270 => !self.mirror_horizontal,
+        };
+
+        let position = position % lines;
+        let line = if reversed {
+            (lines - position) % lines
+        } else {
+            position
+        };
+
+        self.set_start_line(line as u8).await
+    }
+
+    /// Scroll from position `from` to `to` over `frames` steps of equal size,
+    /// waiting `frame_ms` milliseconds after each one, cf.
+    /// `set_scroll_position`. The last step always ends at `to`.
+    pub async fn animate_scroll(
+        &mut self,
+        from: u16,
+        to: u16,
+        frames: u16,
+        frame_ms: u32,
+    ) -> Result<(), Error>
+    where
+        D: DelayNs,
+    {
+        let distance = i32::from(to) - i32::from(from);
+        let frames = frames.max(1);
+
+        for frame in 1..=frames {
+            let step = distance * i32::from(frame) / i32::from(frames);
+            let position =
+                (i32::from(from) + step).rem_euclid(i32::from(DV::START_LINES));
+            self.set_scroll_position(position as u16).await?;
+            self.delay.delay_ms(frame_ms).await;
         }
+
+        Ok(())
     }
 
-    /// Turn the display on or off. The display can be drawn to and retains all
-    /// of its memory even while off.
-    pub async fn display_on(&mut self, on: bool) -> Result<(), DisplayError> {
-        Command::DisplayOn(on).send(&mut self.iface).await
+    /// Set up a continuous hardware scroll of the pages `start_page` to
+    /// `end_page`, moving by one column every `interval`. A non-zero
+    /// `vertical_offset` also moves the whole image up by that many rows
+    /// every step. Any running scroll is stopped first, call `start_scroll`
+    /// to run the new one.
+    ///
+    /// Returns [`Error::Unsupported`] on controllers without the scroll
+    /// commands, such as the SH110x, and [`Error::OutOfBounds`] if the pages
+    /// or offset do not fit on the panel.
+    pub async fn setup_scroll(
+        &mut self,
+        direction: ScrollDirection,
+        start_page: u8,
+        end_page: u8,
+        interval: NFrames,
+        vertical_offset: u8,
+    ) -> Result<(), Error> {
+        if !DV::HARDWARE_SCROLL {
+            return Err(Error::Unsupported);
+        }
+        if start_page > end_page
+            || u16::from(end_page) >= DV::HEIGHT / 8
+            || u16::from(vertical_offset) >= DV::HEIGHT
+        {
+            return Err(Error::OutOfBounds);
+        }
+
+        // The scroll parameters must not change while it is running
+        let batch = CommandBatch::new().push(Command::Scroll(false));
+        let batch = if vertical_offset == 0 {
+            // Scroll all columns of the panel
+            let (first_column, last_column) = if DV::SCROLL_COLUMN_WINDOW {
+                let first = DV::COLUMN_OFFSET;
+                (first, first + (DV::WIDTH - 1) as u8)
+            } else {
+                (0x00, 0xFF)
+            };
+            batch.push(Command::HorizontalScrollSetup(
+                direction,
+                start_page,
+                end_page,
+                interval,
+                first_column,
+                last_column,
+            ))
+        } else {
+            batch
+                .push(Command::VerticalScrollArea(0, DV::HEIGHT as u8))
+                .push(Command::DiagonalScrollSetup(
+                    direction,
+                    start_page,
+                    end_page,
+                    interval,
+                    vertical_offset,
+                ))
+        };
+        batch.send(&mut self.iface).await?;
+
+        Ok(())
     }
 
-    /// Set the display contrast
-    pub async fn set_contrast(
+    /// Start the scroll set up with `setup_scroll`
+    pub async fn start_scroll(&mut self) -> Result<(), Error> {
+        if !DV::HARDWARE_SCROLL {
+            return Err(Error::Unsupported);
+        }
+
+        Command::Scroll(true).send(&mut self.iface).await?;
+
+        Ok(())
+    }
+
+    /// Stop a running scroll. The display RAM is left in a scrolled state and
+    /// has to be written again.
+    pub async fn stop_scroll(&mut self) -> Result<(), Error> {
+        if !DV::HARDWARE_SCROLL {
+            return Err(Error::Unsupported);
+        }
+
+        Command::Scroll(false).send(&mut self.iface).await?;
+
+        Ok(())
+    }
+}
+
+#[maybe_async_cfg::maybe(
+    sync(cfg(not(feature = "async")),),
+    async(feature = "async"),
+    keep_self
+)]
+impl<DV, DI, RST, VCC, D> DisplayProperties<DV, DI, RST, VCC, D>
+where
+    DI: WriteOnlyDataCommand,
+    DV: GrayscaleVariant,
+{
+    /// Initialise a grayscale display, running the same power-up sequence as
+    /// `init_column_mode`. The rotation is not sent to the display, it is up
+    /// to the mode to apply it in software.
+    pub async fn init_grayscale(&mut self) -> Result<(), Error>
+    where
+        RST: OutputPin,
+        VCC: OutputPin,
+        D: PowerDelay<RST, VCC>,
+    {
+        self.reset().await?;
+        DV::init_grayscale(
+            &mut self.iface,
+            &self.init_config,
+            &mut self.delay.delay(),
+        )
+        .await?;
+        self.enable_vcc().await
+    }
+
+    /// Send the nibble packed `buffer` to the window spanned by the pixel
+    /// ranges `columns` and `rows`, one row after the other. Returns
+    /// [`Error::OutOfBounds`] if the window does not fit on the panel.
+    pub async fn draw_window(
         &mut self,
-        contrast: u8,
-    ) -> Result<(), DisplayError> {
-        Command::Contrast(contrast).send(&mut self.iface).await
+        columns: (u16, u16),
+        rows: (u16, u16),
+        buffer: &[u8],
+    ) -> Result<(), Error> {
+        if columns.0 >= columns.1
+            || rows.0 >= rows.1
+            || columns.1 > DV::WIDTH
+            || rows.1 > DV::HEIGHT
+        {
+            return Err(Error::OutOfBounds);
+        }
+
+        if DV::WINDOW_WRAPS {
+            DV::set_window(&mut self.iface, columns, rows).await?;
+            self.iface.send_data(DataFormat::U8(buffer)).await?;
+            return Ok(());
+        }
+
+        let row_length = usize::from(columns.1 - columns.0) / 2;
+        for (row, data) in (rows.0..rows.1).zip(buffer.chunks(row_length)) {
+            DV::set_window(&mut self.iface, columns, (row, row + 1)).await?;
+            self.iface.send_data(DataFormat::U8(data)).await?;
+        }
+
+        Ok(())
     }
 }
@@ -0,0 +1,258 @@
+//! Host side rendering of the [`GraphicsMode`] framebuffer, to review screens
+//! without hardware. Requires the `std` feature.
+//!
+//! A [`Snapshot`] is taken in display coordinates, so it honours the rotation
+//! of the display. Mirroring is applied by the controller and is not part of
+//! the framebuffer, so it does not show up in snapshots.
+//!
+//! ```rust,ignore
+//! let snapshot = display.snapshot();
+//! snapshot.save("screen.png")?;
+//! println!("{}", snapshot.to_half_blocks());
+//! ```
+
+extern crate std;
+
+use std::{
+    fs::File,
+    io::{self, BufWriter, Write},
+    path::Path,
+    string::String,
+    vec::Vec,
+};
+
+#[cfg(feature = "async")]
+use display_interface::AsyncWriteOnlyDataCommand as WriteOnlyDataCommand;
+#[cfg(not(feature = "async"))]
+use display_interface::WriteOnlyDataCommand;
+
+use crate::{displays::MonochromeVariant, mode::GraphicsMode};
+
+/// Copy of the framebuffer as seen on the display, one `bool` per pixel with
+/// `true` for a lit pixel
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Snapshot {
+    width: u32,
+    height: u32,
+    pixels: Vec<bool>,
+}
+
+impl<DV, DI, RST, VCC, D> GraphicsMode<DV, DI, RST, VCC, D>
+where
+    DI: WriteOnlyDataCommand,
+    DV: MonochromeVariant,
+{
+    /// Take a snapshot of the buffer, including changes not flushed yet
+    pub fn snapshot(&self) -> Snapshot {
+        let (width, height) = self.get_dimensions();
+        let (width, height) = (u32::from(width), u32::from(height));
+        let pixels = (0..height)
+            .flat_map(|y| (0..width).map(move |x| (x, y)))
+            .map(|(x, y)| self.get_pixel(x, y) == Some(1))
+            .collect();
+
+        Snapshot {
+            width,
+            height,
+            pixels,
+        }
+    }
+}
+
+impl Snapshot {
+    /// Width of the snapshot in pixels
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    /// Height of the snapshot in pixels
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    /// Get whether the pixel at `(x, y)` is lit. Returns `false` outside the
+    /// snapshot.
+    pub fn pixel(&self, x: u32, y: u32) -> bool {
+        x < self.width
+            && y < self.height
+            && self.pixels[(y * self.width + x) as usize]
+    }
+
+    fn rows(&self) -> impl Iterator<Item = &[bool]> {
+        self.pixels.chunks(self.width as usize)
+    }
+
+    /// Rows packed 8 pixels to the byte, most significant bit first, with
+    /// `on` for lit pixels
+    fn packed_rows(&self, on: bool) -> Vec<u8> {
+        self.rows()
+            .flat_map(|row| row.chunks(8))
+            .map(|pixels| {
+                pixels.iter().enumerate().fold(0, |byte, (i, &lit)| {
+                    byte | (u8::from(lit == on) << (7 - i))
+                })
+            })
+            .collect()
+    }
+
+    /// Write a binary PBM image. Lit pixels are white, like on the panel.
+    pub fn write_pbm<W: Write>(&self, mut out: W) -> io::Result<()> {
+        // PBM uses 1 for black
+        write!(out, "P4\n{} {}\n", self.width, self.height)?;
+        out.write_all(&self.packed_rows(false))
+    }
+
+    /// Write a binary 8 bit PGM image. Lit pixels are white.
+    pub fn write_pgm<W: Write>(&self, mut out: W) -> io::Result<()> {
+        write!(out, "P5\n{} {}\n255\n", self.width, self.height)?;
+        let data: Vec<u8> = self
+            .pixels
+            .iter()
+            .map(|&lit| if lit { 0xFF } else { 0x00 })
+            .collect();
+        out.write_all(&data)
+    }
+
+    /// Write a 1 bit grayscale PNG image. Lit pixels are white.
+    pub fn write_png<W: Write>(&self, out: W) -> io::Result<()> {
+        let mut encoder = png::Encoder::new(out, self.width, self.height);
+        encoder.set_color(png::ColorType::Grayscale);
+        encoder.set_depth(png::BitDepth::One);
+
+        let mut writer = encoder.write_header().map_err(io::Error::other)?;
+        writer
+            .write_image_data(&self.packed_rows(true))
+            .map_err(io::Error::other)?;
+        writer.finish().map_err(io::Error::other)
+    }
+
+    /// Save the snapshot to `path`, in the format given by its extension:
+    /// `pbm`, `pgm` or `png`
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {
+        let path = path.as_ref();
+        let extension = path
+            .extension()
+            .and_then(|extension| extension.to_str())
+            .map(str::to_ascii_lowercase);
+        if !matches!(extension.as_deref(), Some("pbm" | "pgm" | "png")) {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "expected a .pbm, .pgm or .png file",
+            ));
+        }
+
+        let mut out = BufWriter::new(File::create(path)?);
+        match extension.as_deref() {
+            Some("pbm") => self.write_pbm(&mut out)?,
+            Some("pgm") => self.write_pgm(&mut out)?,
+            _ => self.write_png(&mut out)?,
+        }
+        out.flush()
+    }
+
+    /// Render as text, one character per pixel: `#` for lit and `.` for dark
+    pub fn to_ascii(&self) -> String {
+        let mut text = String::new();
+        for row in self.rows() {
+            text.extend(row.iter().map(|&lit| if lit { '#' } else { '.' }));
+            text.push('\n');
+        }
+        text
+    }
+
+    /// Render as text with Unicode half blocks, one character for two rows of
+    /// pixels, so the image keeps its aspect ratio in a terminal
+    pub fn to_half_blocks(&self) -> String {
+        let mut text = String::new();
+        for y in (0..self.height).step_by(2) {
+            text.extend((0..self.width).map(|x| {
+                match (self.pixel(x, y), self.pixel(x, y + 1)) {
+                    (true, true) => '█',
+                    (true, false) => '▀',
+                    (false, true) => '▄',
+                    (false, false) => ' ',
+                }
+            }));
+            text.push('\n');
+        }
+        text
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate std;
+
+    use std::vec;
+
+    use super::Snapshot;
+    use crate::{
+        displayrotation::DisplayRotation,
+        displays::{ssd1306::Ssd1306, Screen128x32},
+        mode::GraphicsMode,
+        test_helpers::RecordingInterface,
+        Builder,
+    };
+
+    fn graphics(
+        rotation: DisplayRotation,
+    ) -> GraphicsMode<Ssd1306<Screen128x32>, RecordingInterface> {
+        Builder::new(Ssd1306::<Screen128x32>::new())
+            .with_rotation(rotation)
+            .connect(RecordingInterface::new())
+            .into()
+    }
+
+    /// 3x2 snapshot with the top left and bottom right pixels lit
+    fn corners() -> Snapshot {
+        Snapshot {
+            width: 3,
+            height: 2,
+            pixels: vec![true, false, false, false, false, true],
+        }
+    }
+
+    #[test]
+    fn snapshot_honours_rotation() {
+        let mut display = graphics(DisplayRotation::Rotate90);
+        display.set_pixel(0, 0, 1);
+        display.set_pixel(31, 127, 1);
+
+        let snapshot = display.snapshot();
+        assert_eq!((snapshot.width(), snapshot.height()), (32, 128));
+        assert!(snapshot.pixel(0, 0));
+        assert!(snapshot.pixel(31, 127));
+        assert_eq!(snapshot.pixels.iter().filter(|&&lit| lit).count(), 2);
+    }
+
+    #[test]
+    fn renders_text() {
+        assert_eq!(corners().to_ascii(), "#..\n..#\n");
+        assert_eq!(corners().to_half_blocks(), "▀ ▄\n");
+    }
+
+    #[test]
+    fn writes_netpbm() {
+        let mut pbm = vec![];
+        corners().write_pbm(&mut pbm).unwrap();
+        assert_eq!(pbm, b"P4\n3 2\n\x60\xC0");
+
+        let mut pgm = vec![];
+        corners().write_pgm(&mut pgm).unwrap();
+        assert_eq!(pgm, b"P5\n3 2\n255\n\xFF\0\0\0\0\xFF");
+    }
+
+    #[test]
+    fn writes_png() {
+        let mut data = vec![];
+        corners().write_png(&mut data).unwrap();
+
+        let decoder = png::Decoder::new(data.as_slice());
+        let mut reader = decoder.read_info().unwrap();
+        let mut image = vec![0; reader.output_buffer_size()];
+        let info = reader.next_frame(&mut image).unwrap();
+        assert_eq!((info.width, info.height), (3, 2));
+        assert_eq!(info.bit_depth, png::BitDepth::One);
+        assert_eq!(&image[..2], [0b1000_0000, 0b0010_0000]);
+    }
+}
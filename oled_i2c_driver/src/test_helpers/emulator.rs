@@ -0,0 +1,313 @@
+//! Software model of the display RAM of the monochrome page addressed
+//! controllers (SH1106, SH1107, SH1108, SSD130x), used to check what would
+//! appear on the glass rather than which bytes were sent.
+
+extern crate std;
+
+use std::{env, fmt, fs, path::PathBuf, string::String, vec, vec::Vec};
+
+use display_interface::{
+    AsyncWriteOnlyDataCommand,
+    DataFormat,
+    DisplayError,
+    WriteOnlyDataCommand,
+};
+
+use super::decode;
+use crate::{command::Command, displays::MonochromeVariant};
+
+/// Display interface interpreting the command stream like the controller
+/// would, keeping the contents of its display RAM.
+///
+/// The RAM spans the panel plus `COLUMN_OFFSET` unused columns on either side,
+/// and the `START_LINES` rows the start line can address. The glass is wired
+/// so that the orientation the driver sends for
+/// [`Rotate0`](crate::displayrotation::DisplayRotation::Rotate0), i.e. segment
+/// remap and reversed COM scan, shows RAM column `COLUMN_OFFSET` and row 0 in
+/// the top left corner.
+#[derive(Debug)]
+pub struct EmulatedController {
+    ram: Vec<u8>,
+    ram_columns: usize,
+    ram_rows: usize,
+    width: usize,
+    height: usize,
+    column_offset: usize,
+    large_page_address: bool,
+    page: usize,
+    column: usize,
+    segment_remap: bool,
+    reverse_com_dir: bool,
+    start_line: usize,
+    display_offset: usize,
+    multiplex: usize,
+    display_on: bool,
+    invert: bool,
+    all_on: bool,
+}
+
+impl EmulatedController {
+    /// Controller for the panel of `DV`, in its reset state with cleared RAM
+    pub fn new<DV: MonochromeVariant>() -> Self {
+        let (width, height) = DV::dimensions();
+        let column_offset = usize::from(DV::COLUMN_OFFSET);
+        let ram_columns = usize::from(width) + 2 * column_offset;
+        let ram_rows =
+            usize::from(height.max(DV::START_LINES)).next_multiple_of(8);
+
+        Self {
+            ram: vec![0; ram_columns * ram_rows / 8],
+            ram_columns,
+            ram_rows,
+            width: usize::from(width),
+            height: usize::from(height),
+            column_offset,
+            large_page_address: DV::LARGE_PAGE_ADDRESS,
+            page: 0,
+            column: 0,
+            segment_remap: false,
+            reverse_com_dir: false,
+            start_line: 0,
+            display_offset: 0,
+            multiplex: ram_rows - 1,
+            display_on: false,
+            invert: false,
+            all_on: false,
+        }
+    }
+
+    /// The image on the glass, as seen by someone looking at the panel
+    pub fn visible(&self) -> Image {
+        let mut image = Image::new(self.width, self.height);
+        if !self.display_on {
+            return image;
+        }
+
+        for y in 0..self.height {
+            // COM pins run from the bottom of the glass to the top
+            let com = self.height - 1 - y;
+            let line = if self.reverse_com_dir {
+                self.multiplex.checked_sub(com)
+            } else {
+                Some(com).filter(|&com| com <= self.multiplex)
+            };
+            let Some(line) = line else {
+                continue;
+            };
+            let row =
+                (line + self.start_line + self.display_offset) % self.ram_rows;
+
+            for x in 0..self.width {
+                // Segments run from the right of the glass to the left
+                let segment = self.width - 1 - x;
+                let column = if self.segment_remap {
+                    self.ram_columns - 1 - self.column_offset - segment
+                } else {
+                    self.column_offset + segment
+                };
+                let byte = self.ram[row / 8 * self.ram_columns + column];
+                let lit = byte & (1 << (row % 8)) != 0;
+
+                image.set(x, y, self.all_on || (lit ^ self.invert));
+            }
+        }
+
+        image
+    }
+
+    fn execute(&mut self, command: Command) {
+        match command {
+            Command::ColumnAddressLow(low) => {
+                self.column = self.column & 0xF0 | usize::from(low & 0xF);
+            }
+            Command::ColumnAddressHigh(high) => {
+                self.column = self.column & 0x0F | usize::from(high) << 4;
+            }
+            Command::PageAddress(page) | Command::LargePageAddress(page) => {
+                self.page = usize::from(page);
+            }
+            Command::SegmentRemap(remap) => self.segment_remap = remap,
+            Command::ReverseComDir(reverse) => self.reverse_com_dir = reverse,
+            Command::StartLine(line) | Command::LargeStartLine(line) => {
+                self.start_line = usize::from(line);
+            }
+            Command::DisplayOffset(offset) => {
+                self.display_offset = usize::from(offset);
+            }
+            Command::Multiplex(ratio) => self.multiplex = usize::from(ratio),
+            Command::DisplayOn(on) => self.display_on = on,
+            Command::Invert(invert) => self.invert = invert,
+            Command::AllOn(on) => self.all_on = on,
+            // Timing, power and scrolling do not change the RAM contents
+            _ => {}
+        }
+    }
+
+    fn write(&mut self, bytes: &[u8]) {
+        for &byte in bytes {
+            // Page addressing stops at the end of the page
+            if self.page < self.ram_rows / 8 && self.column < self.ram_columns {
+                self.ram[self.page * self.ram_columns + self.column] = byte;
+            }
+            self.column += 1;
+        }
+    }
+
+    fn receive(
+        &mut self,
+        format: DataFormat<'_>,
+        commands: bool,
+    ) -> Result<(), DisplayError> {
+        let bytes: Vec<u8> = match format {
+            DataFormat::U8(bytes) => bytes.to_vec(),
+            DataFormat::U8Iter(bytes) => bytes.collect(),
+            _ => return Err(DisplayError::DataFormatNotImplemented),
+        };
+
+        if commands {
+            for command in decode(&bytes, self.large_page_address) {
+                self.execute(command);
+            }
+        } else {
+            self.write(&bytes);
+        }
+
+        Ok(())
+    }
+}
+
+impl WriteOnlyDataCommand for EmulatedController {
+    fn send_commands(
+        &mut self,
+        cmd: DataFormat<'_>,
+    ) -> Result<(), DisplayError> {
+        self.receive(cmd, true)
+    }
+
+    fn send_data(&mut self, buf: DataFormat<'_>) -> Result<(), DisplayError> {
+        self.receive(buf, false)
+    }
+}
+
+impl AsyncWriteOnlyDataCommand for EmulatedController {
+    async fn send_commands(
+        &mut self,
+        cmd: DataFormat<'_>,
+    ) -> Result<(), DisplayError> {
+        self.receive(cmd, true)
+    }
+
+    async fn send_data(
+        &mut self,
+        buf: DataFormat<'_>,
+    ) -> Result<(), DisplayError> {
+        self.receive(buf, false)
+    }
+}
+
+/// Monochrome bitmap, row by row from the top left corner
+#[derive(Clone, PartialEq, Eq)]
+pub struct Image {
+    width: usize,
+    height: usize,
+    pixels: Vec<bool>,
+}
+
+impl Image {
+    pub fn new(width: usize, height: usize) -> Self {
+        Self {
+            width,
+            height,
+            pixels: vec![false; width * height],
+        }
+    }
+
+    pub fn get(&self, x: usize, y: usize) -> bool {
+        self.pixels[y * self.width + x]
+    }
+
+    pub fn set(&mut self, x: usize, y: usize, lit: bool) {
+        self.pixels[y * self.width + x] = lit;
+    }
+
+    /// Plain (ASCII) PBM, one line per pixel row so golden files diff well
+    pub fn to_pbm(&self) -> String {
+        let mut pbm = std::format!("P1\n{} {}\n", self.width, self.height);
+        for row in self.pixels.chunks(self.width) {
+            pbm.extend(row.iter().map(|&lit| if lit { '1' } else { '0' }));
+            pbm.push('\n');
+        }
+        pbm
+    }
+
+    /// Parse a plain PBM. Panics if `pbm` is not one.
+    pub fn from_pbm(pbm: &str) -> Self {
+        let mut tokens = pbm
+            .lines()
+            .map(|line| line.split('#').next().unwrap_or_default())
+            .flat_map(str::split_whitespace);
+        assert_eq!(tokens.next(), Some("P1"), "not a plain PBM file");
+        let mut dimension = || -> usize {
+            tokens
+                .next()
+                .and_then(|t| t.parse().ok())
+                .expect("PBM size")
+        };
+        let (width, height) = (dimension(), dimension());
+
+        let pixels: Vec<bool> = tokens
+            .flat_map(str::chars)
+            .map(|bit| match bit {
+                '0' => false,
+                '1' => true,
+                other => panic!("invalid PBM pixel {other:?}"),
+            })
+            .collect();
+        assert_eq!(pixels.len(), width * height, "PBM pixel count");
+
+        Self {
+            width,
+            height,
+            pixels,
+        }
+    }
+}
+
+impl fmt::Debug for Image {
+    /// Draw the image, so a failing comparison shows both pictures
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "{}x{}", self.width, self.height)?;
+        for row in self.pixels.chunks(self.width) {
+            for &lit in row {
+                f.write_str(if lit { "#" } else { "." })?;
+            }
+            writeln!(f)?;
+        }
+        Ok(())
+    }
+}
+
+/// Compare `image` against `tests/golden/<name>.pbm`. Set `UPDATE_GOLDEN=1`
+/// to write the file from `image` instead, then check the result by eye.
+pub fn assert_golden(name: &str, image: &Image) {
+    let path: PathBuf = [env!("CARGO_MANIFEST_DIR"), "tests", "golden"]
+        .iter()
+        .collect::<PathBuf>()
+        .join(name)
+        .with_extension("pbm");
+
+    if env::var_os("UPDATE_GOLDEN").is_some() {
+        fs::write(&path, image.to_pbm()).unwrap();
+        return;
+    }
+
+    let golden = fs::read_to_string(&path).unwrap_or_else(|e| {
+        panic!("cannot read {}: {e}", path.display());
+    });
+    assert_eq!(
+        *image,
+        Image::from_pbm(&golden),
+        "image differs from {}",
+        path.display()
+    );
+}
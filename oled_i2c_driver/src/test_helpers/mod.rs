@@ -0,0 +1,376 @@
+//! Test doubles for host tests: a display interface that records everything
+//! sent to it, a decoder turning the recorded command bytes back into
+//! [`Command`]s, and an emulated controller for golden image tests.
+
+extern crate std;
+
+mod emulator;
+
+use core::cell::RefCell;
+#[cfg(feature = "async")]
+use core::{
+    future::Future,
+    pin::pin,
+    task::{Context, Poll, Waker},
+};
+use std::{rc::Rc, vec, vec::Vec};
+
+use display_interface::{
+    AsyncWriteOnlyDataCommand,
+    DataFormat,
+    DisplayError,
+    WriteOnlyDataCommand,
+};
+#[cfg(feature = "async")]
+use embedded_hal_async::delay::DelayNs;
+#[cfg(not(feature = "async"))]
+use hal::delay::DelayNs;
+use hal::digital::{ErrorType, OutputPin};
+
+pub use self::emulator::{assert_golden, EmulatedController, Image};
+use crate::{
+    command::{Command, NFrames, ScrollDirection, VcomhLevel},
+    displays::MonochromeVariant,
+};
+
+/// One call to the display interface
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Transfer {
+    /// Bytes sent with `send_commands`
+    Commands(Vec<u8>),
+    /// Bytes sent with `send_data`
+    Data(Vec<u8>),
+}
+
+/// Display interface keeping every transfer, for both the blocking and the
+/// async trait
+#[derive(Debug, Default)]
+pub struct RecordingInterface {
+    pub transfers: Vec<Transfer>,
+}
+
+impl RecordingInterface {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// All command bytes in the order they were sent
+    pub fn command_bytes(&self) -> Vec<u8> {
+        self.transfers
+            .iter()
+            .filter_map(|transfer| match transfer {
+                Transfer::Commands(bytes) => Some(bytes.as_slice()),
+                Transfer::Data(_) => None,
+            })
+            .flatten()
+            .copied()
+            .collect()
+    }
+
+    /// All commands sent, decoded as understood by the controller of `DV`
+    pub fn commands<DV: MonochromeVariant>(&self) -> Vec<Command> {
+        decode(&self.command_bytes(), DV::LARGE_PAGE_ADDRESS)
+    }
+
+    /// Number of `send_commands` calls, i.e. bus transactions for commands
+    pub fn command_transfers(&self) -> usize {
+        self.transfers
+            .iter()
+            .filter(|transfer| matches!(transfer, Transfer::Commands(_)))
+            .count()
+    }
+
+    fn record(
+        &mut self,
+        format: DataFormat<'_>,
+        transfer: fn(Vec<u8>) -> Transfer,
+    ) -> Result<(), DisplayError> {
+        let bytes = match format {
+            DataFormat::U8(bytes) => bytes.to_vec(),
+            DataFormat::U8Iter(bytes) => bytes.collect(),
+            _ => return Err(DisplayError::DataFormatNotImplemented),
+        };
+        self.transfers.push(transfer(bytes));
+
+        Ok(())
+    }
+}
+
+impl WriteOnlyDataCommand for RecordingInterface {
+    fn send_commands(
+        &mut self,
+        cmd: DataFormat<'_>,
+    ) -> Result<(), DisplayError> {
+        self.record(cmd, Transfer::Commands)
+    }
+
+    fn send_data(&mut self, buf: DataFormat<'_>) -> Result<(), DisplayError> {
+        self.record(buf, Transfer::Data)
+    }
+}
+
+impl AsyncWriteOnlyDataCommand for RecordingInterface {
+    async fn send_commands(
+        &mut self,
+        cmd: DataFormat<'_>,
+    ) -> Result<(), DisplayError> {
+        self.record(cmd, Transfer::Commands)
+    }
+
+    async fn send_data(
+        &mut self,
+        buf: DataFormat<'_>,
+    ) -> Result<(), DisplayError> {
+        self.record(buf, Transfer::Data)
+    }
+}
+
+/// Something the driver did during a power sequence
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PowerEvent {
+    /// Transfer over the display interface
+    Transfer(Transfer),
+    /// Level set on the named pin
+    Pin(&'static str, bool),
+    /// Wait in milliseconds
+    DelayMs(u32),
+}
+
+/// Display interface and delay sharing one log with the pins made by
+/// [`pin`](PowerLog::pin), so the order of commands, pin changes and waits can
+/// be checked. Clones share the log.
+#[derive(Debug, Clone, Default)]
+pub struct PowerLog(Rc<RefCell<Vec<PowerEvent>>>);
+
+impl PowerLog {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Output pin logging its level changes under `name`
+    pub fn pin(&self, name: &'static str) -> LoggedPin {
+        LoggedPin {
+            name,
+            log: self.clone(),
+        }
+    }
+
+    pub fn events(&self) -> Vec<PowerEvent> {
+        self.0.borrow().clone()
+    }
+
+    fn push(&self, event: PowerEvent) {
+        self.0.borrow_mut().push(event);
+    }
+
+    fn record(
+        &mut self,
+        format: DataFormat<'_>,
+        transfer: fn(Vec<u8>) -> Transfer,
+    ) -> Result<(), DisplayError> {
+        let mut iface = RecordingInterface::new();
+        iface.record(format, transfer)?;
+        self.push(PowerEvent::Transfer(iface.transfers.remove(0)));
+
+        Ok(())
+    }
+}
+
+impl WriteOnlyDataCommand for PowerLog {
+    fn send_commands(
+        &mut self,
+        cmd: DataFormat<'_>,
+    ) -> Result<(), DisplayError> {
+        self.record(cmd, Transfer::Commands)
+    }
+
+    fn send_data(&mut self, buf: DataFormat<'_>) -> Result<(), DisplayError> {
+        self.record(buf, Transfer::Data)
+    }
+}
+
+impl AsyncWriteOnlyDataCommand for PowerLog {
+    async fn send_commands(
+        &mut self,
+        cmd: DataFormat<'_>,
+    ) -> Result<(), DisplayError> {
+        self.record(cmd, Transfer::Commands)
+    }
+
+    async fn send_data(
+        &mut self,
+        buf: DataFormat<'_>,
+    ) -> Result<(), DisplayError> {
+        self.record(buf, Transfer::Data)
+    }
+}
+
+#[cfg(not(feature = "async"))]
+impl DelayNs for PowerLog {
+    fn delay_ns(&mut self, ns: u32) {
+        self.push(PowerEvent::DelayMs(ns.div_ceil(1_000_000)));
+    }
+
+    fn delay_ms(&mut self, ms: u32) {
+        self.push(PowerEvent::DelayMs(ms));
+    }
+}
+
+#[cfg(feature = "async")]
+impl DelayNs for PowerLog {
+    async fn delay_ns(&mut self, ns: u32) {
+        self.push(PowerEvent::DelayMs(ns.div_ceil(1_000_000)));
+    }
+
+    async fn delay_ms(&mut self, ms: u32) {
+        self.push(PowerEvent::DelayMs(ms));
+    }
+}
+
+/// Pin made by [`PowerLog::pin`]
+#[derive(Debug)]
+pub struct LoggedPin {
+    name: &'static str,
+    log: PowerLog,
+}
+
+impl ErrorType for LoggedPin {
+    type Error = core::convert::Infallible;
+}
+
+impl OutputPin for LoggedPin {
+    fn set_low(&mut self) -> Result<(), Self::Error> {
+        self.log.push(PowerEvent::Pin(self.name, false));
+        Ok(())
+    }
+
+    fn set_high(&mut self) -> Result<(), Self::Error> {
+        self.log.push(PowerEvent::Pin(self.name, true));
+        Ok(())
+    }
+}
+
+/// Turn command bytes back into [`Command`]s. `large_page_address` selects
+/// the two byte SH1108 page address over the single byte one. Panics on bytes
+/// that are not the encoding of a `Command`.
+pub fn decode(mut bytes: &[u8], large_page_address: bool) -> Vec<Command> {
+    let mut commands = vec![];
+
+    while let [first, rest @ ..] = bytes {
+        let param = |n: usize| {
+            *rest.get(n).unwrap_or_else(|| {
+                panic!("missing parameter {n} of command {first:#04X}")
+            })
+        };
+
+        let (command, len) = match *first {
+            0x00..=0x0F => (Command::ColumnAddressLow(first & 0xF), 1),
+            0x10..=0x1F => (Command::ColumnAddressHigh(first & 0xF), 1),
+            0x20 | 0x21 => (Command::AddressMode(first & 1 != 0), 1),
+            0x26 | 0x27 => (
+                Command::HorizontalScrollSetup(
+                    scroll_direction(first - 0x26),
+                    param(1),
+                    param(3),
+                    n_frames(param(2)),
+                    param(4),
+                    param(5),
+                ),
+                7,
+            ),
+            0x29 | 0x2A => (
+                Command::DiagonalScrollSetup(
+                    scroll_direction(first - 0x29),
+                    param(1),
+                    param(3),
+                    n_frames(param(2)),
+                    param(4),
+                ),
+                6,
+            ),
+            0x2E | 0x2F => (Command::Scroll(first & 1 != 0), 1),
+            0x40..=0x7F => (Command::StartLine(first & 0x3F), 1),
+            0x81 => (Command::Contrast(param(0)), 2),
+            0x8D => (Command::SsdChargePump(param(0) & 0x4 != 0), 2),
+            0xA0 | 0xA1 => (Command::SegmentRemap(first & 1 != 0), 1),
+            0xA3 => (Command::VerticalScrollArea(param(0), param(1)), 3),
+            0xA4 | 0xA5 => (Command::AllOn(first & 1 != 0), 1),
+            0xA6 | 0xA7 => (Command::Invert(first & 1 != 0), 1),
+            0xA8 => (Command::Multiplex(param(0)), 2),
+            0xA9 => (Command::DisplayResolution(param(0)), 2),
+            0xAD => (Command::ChargePump(param(0) & 1 != 0), 2),
+            0xAE | 0xAF => (Command::DisplayOn(first & 1 != 0), 1),
+            0xB0 if large_page_address => {
+                (Command::LargePageAddress(param(0)), 2)
+            }
+            0xB0..=0xBF => (Command::PageAddress(first & 0xF), 1),
+            0xC0..=0xCF => (Command::ReverseComDir(first & 0x8 != 0), 1),
+            0xD3 => (Command::DisplayOffset(param(0)), 2),
+            0xD5 => {
+                (Command::DisplayClockDiv(param(0) >> 4, param(0) & 0xF), 2)
+            }
+            0xD9 => {
+                (Command::PreChargePeriod(param(0) & 0xF, param(0) >> 4), 2)
+            }
+            0xDA => (Command::ComPinConfig(param(0) & 0x10 != 0), 2),
+            0xDB => (Command::VcomhDeselect(vcomh_level(param(0) >> 4)), 2),
+            0xDC => (Command::LargeStartLine(param(0)), 2),
+            0xE3 => (Command::Noop, 1),
+            other => panic!("unknown command byte {other:#04X}"),
+        };
+
+        commands.push(command);
+        bytes = &bytes[len..];
+    }
+
+    commands
+}
+
+fn scroll_direction(value: u8) -> ScrollDirection {
+    match value {
+        0 => ScrollDirection::Right,
+        _ => ScrollDirection::Left,
+    }
+}
+
+fn n_frames(value: u8) -> NFrames {
+    match value & 0x7 {
+        0b111 => NFrames::F2,
+        0b100 => NFrames::F3,
+        0b101 => NFrames::F4,
+        0b000 => NFrames::F5,
+        0b110 => NFrames::F25,
+        0b001 => NFrames::F64,
+        0b010 => NFrames::F128,
+        _ => NFrames::F256,
+    }
+}
+
+fn vcomh_level(value: u8) -> VcomhLevel {
+    match value {
+        0b001 => VcomhLevel::V065,
+        0b010 => VcomhLevel::V077,
+        0b011 => VcomhLevel::V083,
+        0b100 => VcomhLevel::Auto,
+        other => panic!("unknown VCOMH level {other:#04b}"),
+    }
+}
+
+/// Run a driver call to completion. Nothing waits on the recording interface,
+/// so polling until ready is enough.
+#[cfg(feature = "async")]
+pub fn block_on<F: Future>(future: F) -> F::Output {
+    let mut future = pin!(future);
+    let mut cx = Context::from_waker(Waker::noop());
+    loop {
+        if let Poll::Ready(output) = future.as_mut().poll(&mut cx) {
+            return output;
+        }
+    }
+}
+
+/// Blocking driver calls are already done, pass their result through
+#[cfg(not(feature = "async"))]
+pub fn block_on<T>(output: T) -> T {
+    output
+}